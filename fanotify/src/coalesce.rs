@@ -0,0 +1,218 @@
+//! Coalescing of repeated events, to limit the rate passed on to consumers.
+//!
+//! A busy file produces a flood of (e.g.) `ACCESS` and `MODIFY` events. The
+//!  kernel only merges events still queued, so most are reported separately.
+//!  [Coalescer] holds events for a time window, merging any later event for the
+//!  same file and process into the first by OR-ing the masks.
+//!
+//! Permission, rename and filesystem error events are never held or merged,
+//!  since they must be responded to or carry details that cannot be merged.
+
+use crate::{flags::EventFlags, Event, EventError, EventFile, Fanotify, FileHandle, Info, Result};
+
+use std::collections::{HashMap, VecDeque};
+use std::ffi::OsString;
+use std::os::unix::fs::MetadataExt;
+use std::time::{Duration, Instant};
+
+/// Identity of the file (and process) an event is for.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+enum Key {
+	/// Event with a descriptor, identified by device and inode.
+	Inode { dev: u64, ino: u64, pid: u32 },
+	/// Event with a file handle, and entry name if reported.
+	Handle { fh: FileHandle, name: Option<OsString>, pid: u32 },
+}
+
+impl Key {
+	/// Key for `evt`, or `None` if it must not be coalesced.
+	fn of(evt: &Event) -> Option<Self> {
+		if evt.mask.intersects(EventFlags::PERM | EventFlags::RENAME | EventFlags::FS_ERROR) {
+			return None;
+		}
+
+		match &evt.file {
+			EventFile::Fd(f) => {
+				let meta = f.metadata().ok()?;
+				Some(Self::Inode { dev: meta.dev(), ino: meta.ino(), pid: evt.pid })
+			},
+			EventFile::Fh(fh) => {
+				let name = evt.info.iter().find_map(|i| match i {
+					Info::DfidName(_, name) => Some(name.clone()),
+					_ => None,
+				});
+				Some(Self::Handle { fh: fh.clone(), name, pid: evt.pid })
+			},
+			EventFile::Empty => None,
+		}
+	}
+}
+
+/// Counters kept by a [Coalescer].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct CoalesceStats {
+	/// Events read from the group.
+	pub received: u64,
+	/// Events merged into an earlier event.
+	pub merged: u64,
+	/// Events dropped because too many were held.
+	pub dropped: u64,
+	/// Events returned to the caller.
+	pub emitted: u64,
+	/// Times the kernel event queue overflowed.
+	pub overflows: u64,
+}
+
+/// Merges events for the same file and process within a time window.
+///
+/// Each held event is returned once the window since it was first read has
+///  elapsed, in the order first read. A zero window disables coalescing.
+#[derive(Debug)]
+pub struct Coalescer {
+	fan: Fanotify,
+	window: Duration,
+	max_pending: usize,
+	/// Events not held, or whose window has elapsed.
+	ready: VecDeque<Event>,
+	/// Held events, with the time first read.
+	pending: HashMap<Key, (Instant, Event)>,
+	/// Keys of held events, oldest first.
+	order: VecDeque<Key>,
+	stats: CoalesceStats,
+}
+
+impl Coalescer {
+	/// Coalesce events from `fan` within `window`.
+	pub fn new(fan: Fanotify, window: Duration) -> Self {
+		Self {
+			fan,
+			window,
+			max_pending: 1024,
+			ready: VecDeque::new(),
+			pending: HashMap::new(),
+			order: VecDeque::new(),
+			stats: CoalesceStats::default(),
+		}
+	}
+
+	/// Limit the number of held events (default 1024).
+	///
+	/// Once reached, events for files not already held are dropped, and
+	///  counted in [CoalesceStats::dropped].
+	pub fn max_pending(mut self, max: usize) -> Self {
+		self.max_pending = max;
+		self
+	}
+
+	pub fn get_ref(&self) -> &Fanotify {
+		&self.fan
+	}
+
+	/// Get the group, e.g. to add marks.
+	pub fn get_mut(&mut self) -> &mut Fanotify {
+		&mut self.fan
+	}
+
+	/// Get the group, discarding any held events.
+	pub fn into_inner(self) -> Fanotify {
+		self.fan
+	}
+
+	pub fn stats(&self) -> CoalesceStats {
+		self.stats
+	}
+
+	/// Number of events currently held.
+	pub fn pending(&self) -> usize {
+		self.pending.len()
+	}
+
+	/// Return the next event, waiting for one if necessary.
+	///
+	/// Permission events are returned immediately, and must be responded to
+	///  using [Coalescer::get_ref]. Errors from the group are passed on, with
+	///  queue overflows also counted in [CoalesceStats::overflows].
+	pub fn next_event(&mut self) -> Result<Event> {
+		loop {
+			if let Some(evt) = self.ready.pop_front() {
+				self.stats.emitted += 1;
+				return Ok(evt);
+			}
+
+			let now = Instant::now();
+			self.expire(now);
+			if !self.ready.is_empty() {
+				continue;
+			}
+
+			// Wait no longer than the oldest held event is due.
+			let timeout = self.order.front()
+				.map(|k| (self.pending[k].0 + self.window).saturating_duration_since(now));
+			if !self.fan.wait_for_events(timeout)? {
+				continue;
+			}
+
+			let batch = match self.fan.read_batch() {
+				Ok(b) => b,
+				Err(e) => {
+					if EventError::from_io(&e) == Some(EventError::Overflow) {
+						self.stats.overflows += 1;
+					}
+					return Err(e);
+				},
+			};
+			for evt in batch {
+				self.push(evt, Instant::now());
+			}
+		}
+	}
+
+	/// Return all held events immediately, oldest first.
+	pub fn flush(&mut self) -> Vec<Event> {
+		self.expire_while(|_| true);
+		self.stats.emitted += self.ready.len() as u64;
+		self.ready.drain(..).collect()
+	}
+
+	/// Hold or merge a newly read event.
+	fn push(&mut self, evt: Event, now: Instant) {
+		self.stats.received += 1;
+
+		let key = match Key::of(&evt) {
+			Some(k) if !self.window.is_zero() => k,
+			_ => {
+				self.ready.push_back(evt);
+				return;
+			},
+		};
+
+		if let Some((_, held)) = self.pending.get_mut(&key) {
+			held.mask |= evt.mask;
+			self.stats.merged += 1;
+		} else if self.pending.len() >= self.max_pending {
+			self.stats.dropped += 1;
+		} else {
+			self.order.push_back(key.clone());
+			self.pending.insert(key, (now, evt));
+		}
+	}
+
+	/// Move held events whose window has elapsed to the ready queue.
+	fn expire(&mut self, now: Instant) {
+		let window = self.window;
+		self.expire_while(|first| first + window <= now);
+	}
+
+	fn expire_while<F: Fn(Instant) -> bool>(&mut self, due: F) {
+		while let Some(key) = self.order.front() {
+			if !due(self.pending[key].0) {
+				break;
+			}
+
+			let key = self.order.pop_front().unwrap();
+			if let Some((_, evt)) = self.pending.remove(&key) {
+				self.ready.push_back(evt);
+			}
+		}
+	}
+}
@@ -0,0 +1,270 @@
+//! In-memory event source, for testing consumers without a kernel group.
+//!
+//! [pair] creates a [Fanotify] reading from a socket instead of a fanotify
+//!  descriptor, with a [FakeSource] to send it events. Events are built with
+//!  [FakeEvent] and serialized exactly as the kernel would, so are decoded by
+//!  the same code as real events. Responses to permission events are written
+//!  back to the source, and can be checked with [FakeSource::response].
+//!
+//! Marking is not possible, since the descriptor is not a fanotify group.
+
+use crate::{flags::*, struct_bytes, sys, AuditRule, Fanotify, FileHandle, Result, BUF_SIZE};
+
+use std::ffi::OsStr;
+use std::os::fd::{AsRawFd, IntoRawFd, OwnedFd};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::net::UnixDatagram;
+use std::{fs, io, mem};
+
+/// Create a [Fanotify] reading events from the returned [FakeSource].
+///
+/// The flags determine how events are decoded (e.g. `REPORT_TID`), and
+///  whether reads block (`NONBLOCK`).
+pub fn pair(flags: &InitFlags) -> Result<(Fanotify, FakeSource)> {
+	flags.validate()?;
+
+	// Datagrams preserve boundaries, so each send is returned by a single read.
+	let (group, source) = UnixDatagram::pair()?;
+	group.set_nonblocking(flags.contains(InitFlags::NONBLOCK))?;
+
+	let fan = Fanotify::from_file(fs::File::from(OwnedFd::from(group)), flags);
+	Ok((fan, FakeSource { sock: source }))
+}
+
+/// Sending side of a fake group. See [pair].
+#[derive(Debug)]
+pub struct FakeSource {
+	sock: UnixDatagram,
+}
+impl FakeSource {
+	/// Send events, to be returned together by a single read of the group.
+	///
+	/// Fails with [io::ErrorKind::InvalidInput] if the events would not fit
+	///  in the group read buffer.
+	pub fn send<I: IntoIterator<Item = FakeEvent>>(&self, events: I) -> Result<()> {
+		let buf: Vec<u8> = events.into_iter().flat_map(FakeEvent::into_bytes).collect();
+		self.send_raw(&buf)
+	}
+
+	/// Send raw data to be returned by a single read, e.g. to test malformed
+	///  events.
+	///
+	/// Any descriptors within must be owned by nothing else, since the decoded
+	///  events will close them.
+	pub fn send_raw(&self, buf: &[u8]) -> Result<()> {
+		if buf.len() > BUF_SIZE {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "Events exceed read buffer size."));
+		}
+
+		self.sock.send(buf)?;
+		Ok(())
+	}
+
+	/// Wait for the next response written with [Fanotify::respond].
+	pub fn response(&self) -> Result<FakeResponse> {
+		const RESP_SIZE: usize = mem::size_of::<sys::response>();
+		const RULE_SIZE: usize = mem::size_of::<sys::response_info_audit_rule>();
+
+		let mut buf = [0; RESP_SIZE + RULE_SIZE];
+		let n = self.sock.recv(&mut buf)?;
+		if n < RESP_SIZE {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, "Response too short."));
+		}
+
+		// SAFETY: Lengths checked. Unaligned read required since byte array.
+		let resp = unsafe { (buf.as_ptr() as *const sys::response).read_unaligned() };
+		let audit_rule = (n >= RESP_SIZE + RULE_SIZE).then(|| {
+			// SAFETY: As above.
+			let rule = unsafe { (buf[RESP_SIZE..].as_ptr() as *const sys::response_info_audit_rule).read_unaligned() };
+			AuditRule {
+				rule_number: rule.rule_number,
+				subj_trust: rule.subj_trust,
+				obj_trust: rule.obj_trust,
+			}
+		});
+
+		Ok(FakeResponse {
+			fd: resp.fd as i32,
+			response: resp.response as i32,
+			audit_rule,
+		})
+	}
+}
+
+/// Response written to a [FakeSource].
+#[derive(Clone, Copy, Debug)]
+pub struct FakeResponse {
+	/// Event descriptor responded to.
+	pub fd: i32,
+	/// Raw response bits (e.g. [sys::FAN_ALLOW]).
+	pub response: i32,
+	pub audit_rule: Option<AuditRule>,
+}
+
+/// A synthetic event, serialized as read from a fanotify group.
+#[derive(Debug)]
+pub struct FakeEvent {
+	mask: EventFlags,
+	fd: Option<OwnedFd>,
+	pid: u32,
+	/// Serialized info records.
+	info: Vec<u8>,
+	/// Descriptors referred to by `info`, released once serialized.
+	info_fds: Vec<OwnedFd>,
+}
+impl FakeEvent {
+	/// Event with no descriptor (as in groups reporting file handles).
+	pub fn new(mask: EventFlags, pid: u32) -> Self {
+		Self {
+			mask,
+			fd: None,
+			pid,
+			info: Vec::new(),
+			info_fds: Vec::new(),
+		}
+	}
+
+	/// Descriptor for the event object, owned by the decoded event.
+	pub fn fd(mut self, fd: OwnedFd) -> Self {
+		self.fd = Some(fd);
+		self
+	}
+
+	/// Add a `FID` record.
+	pub fn fid(self, fh: &FileHandle) -> Self {
+		self.fid_record(sys::FAN_EVENT_INFO_TYPE_FID, fh, None)
+	}
+
+	/// Add a `DFID` record.
+	pub fn dfid(self, fh: &FileHandle) -> Self {
+		self.fid_record(sys::FAN_EVENT_INFO_TYPE_DFID, fh, None)
+	}
+
+	/// Add a `DFID_NAME` record.
+	pub fn dfid_name<S: AsRef<OsStr>>(self, fh: &FileHandle, name: S) -> Self {
+		self.fid_record(sys::FAN_EVENT_INFO_TYPE_DFID_NAME, fh, Some(name.as_ref()))
+	}
+
+	/// Add an `OLD_DFID_NAME` record, as with `FAN_RENAME`.
+	pub fn old_dfid_name<S: AsRef<OsStr>>(self, fh: &FileHandle, name: S) -> Self {
+		self.fid_record(sys::FAN_EVENT_INFO_TYPE_OLD_DFID_NAME, fh, Some(name.as_ref()))
+	}
+
+	/// Add a `NEW_DFID_NAME` record, as with `FAN_RENAME`.
+	pub fn new_dfid_name<S: AsRef<OsStr>>(self, fh: &FileHandle, name: S) -> Self {
+		self.fid_record(sys::FAN_EVENT_INFO_TYPE_NEW_DFID_NAME, fh, Some(name.as_ref()))
+	}
+
+	/// Add a `PIDFD` record, with `None` as for an exited process.
+	pub fn pidfd(mut self, pidfd: Option<OwnedFd>) -> Self {
+		let raw = match pidfd {
+			Some(fd) => {
+				let raw = fd.as_raw_fd();
+				self.info_fds.push(fd);
+				raw
+			},
+			None => sys::FAN_NOPIDFD,
+		};
+		let info = sys::event_info_pidfd {
+			hdr: header(sys::FAN_EVENT_INFO_TYPE_PIDFD, mem::size_of::<sys::event_info_pidfd>()),
+			pidfd: raw,
+		};
+
+		// SAFETY: No padding in struct.
+		self.info.extend_from_slice(unsafe { struct_bytes(&info) });
+		self
+	}
+
+	/// Add an `ERROR` record, as with `FAN_FS_ERROR`.
+	pub fn error(mut self, errno: i32, count: u32) -> Self {
+		let info = sys::event_info_error {
+			hdr: header(sys::FAN_EVENT_INFO_TYPE_ERROR, mem::size_of::<sys::event_info_error>()),
+			error: errno as u32,
+			error_count: count,
+		};
+
+		// SAFETY: No padding in struct.
+		self.info.extend_from_slice(unsafe { struct_bytes(&info) });
+		self
+	}
+
+	/// Add a `MNT` record, as with `MNT_ATTACH` and `MNT_DETACH`.
+	pub fn mnt(mut self, mnt_id: u64) -> Self {
+		// Struct has padding after the header, so serialize by field.
+		let hdr = header(sys::FAN_EVENT_INFO_TYPE_MNT, mem::size_of::<sys::event_info_mnt>());
+		let start = self.info.len();
+		// SAFETY: No padding in struct.
+		self.info.extend_from_slice(unsafe { struct_bytes(&hdr) });
+		self.info.resize(start + mem::offset_of!(sys::event_info_mnt, mnt_id), 0);
+		self.info.extend_from_slice(&mnt_id.to_ne_bytes());
+		self
+	}
+
+	/// Add an arbitrary (possibly malformed) info record.
+	pub fn info_raw(mut self, record: &[u8]) -> Self {
+		self.info.extend_from_slice(record);
+		self
+	}
+
+	/// Serialize the event.
+	///
+	/// Ownership of any descriptors passes to whatever decodes the bytes, so
+	///  they are leaked if never decoded.
+	pub fn into_bytes(self) -> Vec<u8> {
+		let meta = sys::event_metadata {
+			event_len: (mem::size_of::<sys::event_metadata>() + self.info.len()) as u32,
+			vers: sys::FANOTIFY_METADATA_VERSION as u8,
+			_reserved: 0,
+			_metadata_len: mem::size_of::<sys::event_metadata>() as u16,
+			mask: self.mask.bits() as u32 as u64,
+			fd: self.fd.map_or(sys::FAN_NOFD, IntoRawFd::into_raw_fd),
+			pid: self.pid,
+		};
+		for fd in self.info_fds {
+			let _ = fd.into_raw_fd();
+		}
+
+		// SAFETY: No padding in struct.
+		let mut buf = unsafe { struct_bytes(&meta) }.to_vec();
+		buf.extend_from_slice(&self.info);
+
+		buf
+	}
+
+	fn fid_record(mut self, info_type: i32, fh: &FileHandle, name: Option<&OsStr>) -> Self {
+		const FID_SIZE: usize = mem::size_of::<sys::event_info_fid>();
+
+		// Records are padded to 4 byte alignment, after the null-terminated name.
+		let name_len = name.map_or(0, |n| n.len() + 1);
+		let len = (FID_SIZE + fh.bytes().len() + name_len).next_multiple_of(4);
+		let fid = sys::event_info_fid {
+			hdr: header(info_type, len),
+			fsid: fh.fsid(),
+			file_handle: sys::file_handle {
+				handle_bytes: fh.bytes().len() as u32,
+				handle_type: fh.handle_type(),
+				handle: [],
+			},
+		};
+
+		let start = self.info.len();
+		// SAFETY: No padding in struct.
+		self.info.extend_from_slice(unsafe { struct_bytes(&fid) });
+		self.info.extend_from_slice(fh.bytes());
+		if let Some(name) = name {
+			self.info.extend_from_slice(name.as_bytes());
+			self.info.push(0);
+		}
+		self.info.resize(start + len, 0);
+
+		self
+	}
+}
+
+fn header(info_type: i32, len: usize) -> sys::event_info_header {
+	sys::event_info_header {
+		info_type: info_type as u8,
+		_pad: 0,
+		len: len as u16,
+	}
+}
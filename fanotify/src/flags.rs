@@ -1,94 +1,112 @@
 use crate::sys;
 
-use std::default::Default;
+use bitflags::bitflags;
 
-/// Source: fanotify_init
-#[derive(Clone, Copy, Debug, Default)]
-pub struct InitFlags {
-	/// Mutually exclusive with content and notif
-	pub pre_content: bool,
-	/// Mutually exclusive with pre-content and notif
-	pub content: bool,
-	/// Mutually exclusive with pre-content and content
-	pub notif: bool,
-	pub cloexec: bool,
-	pub nonblock: bool,
-	/// Requires CAP_SYS_ADMIN
-	pub unlimited_queue: bool,
-	/// Requires CAP_SYS_ADMIN
-	pub unlimited_marks: bool,
-	/// Requires CAP_SYS_ADMIN
-	pub report_tid: bool,
-	/// Requires CAP_AUDIT_WRITE
-	pub enable_audit: bool,
-	/// Mutually exclusive with pre-content and content
-	pub report_fid: bool,
-	pub report_dir_fid: bool,
-	// Must be provided with report_dir_fid, so use report_dfid_name.
-	//pub report_name: bool,
-	/// Synonym for (report_dir_fid | report_name)
-	pub report_dfid_name: bool,
-	// Must be provided in conjunction with report_fid and report_dfid_name, so use report_dfid_name_target.
-	//pub report_target_fid: bool,
-	/// Synonym for (report_dfid_name | report_fid | report_target_fid)
-	pub report_dfid_name_target: bool,
-	/// Mutually exclusive with report_tid
-	pub report_pidfd: bool
+use std::{fmt, io};
+
+// Formatting greedily names flags in declaration order, so composites are
+//  declared before their components.
+
+bitflags! {
+	/// Flags for `fanotify_init()`.
+	///
+	/// The `CLASS_*` values are not bitwise flags, and at most one may be given
+	///  (`CLASS_NOTIF` is zero, and so the default).
+	///
+	/// Source: fanotify_init
+	#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+	pub struct InitFlags: i32 {
+		/// Mutually exclusive with CLASS_CONTENT and CLASS_NOTIF
+		const CLASS_PRE_CONTENT = sys::FAN_CLASS_PRE_CONTENT;
+		/// Mutually exclusive with CLASS_PRE_CONTENT and CLASS_NOTIF
+		const CLASS_CONTENT = sys::FAN_CLASS_CONTENT;
+		/// Mutually exclusive with CLASS_PRE_CONTENT and CLASS_CONTENT
+		const CLASS_NOTIF = sys::FAN_CLASS_NOTIF;
+		const CLOEXEC = sys::FAN_CLOEXEC;
+		const NONBLOCK = sys::FAN_NONBLOCK;
+		/// Requires CAP_SYS_ADMIN
+		const UNLIMITED_QUEUE = sys::FAN_UNLIMITED_QUEUE;
+		/// Requires CAP_SYS_ADMIN
+		const UNLIMITED_MARKS = sys::FAN_UNLIMITED_MARKS;
+		/// Requires CAP_AUDIT_WRITE
+		const ENABLE_AUDIT = sys::FAN_ENABLE_AUDIT;
+		/// Synonym for (REPORT_DFID_NAME | REPORT_FID | REPORT_TARGET_FID)
+		const REPORT_DFID_NAME_TARGET = sys::FAN_REPORT_DFID_NAME_TARGET;
+		/// Synonym for (REPORT_DIR_FID | REPORT_NAME)
+		const REPORT_DFID_NAME = sys::FAN_REPORT_DFID_NAME;
+		/// Mutually exclusive with REPORT_TID
+		const REPORT_PIDFD = sys::FAN_REPORT_PIDFD;
+		/// Requires CAP_SYS_ADMIN. Mutually exclusive with REPORT_PIDFD
+		const REPORT_TID = sys::FAN_REPORT_TID;
+		/// Mutually exclusive with CLASS_PRE_CONTENT and CLASS_CONTENT
+		const REPORT_FID = sys::FAN_REPORT_FID;
+		const REPORT_DIR_FID = sys::FAN_REPORT_DIR_FID;
+		/// Requires REPORT_DIR_FID
+		const REPORT_NAME = sys::FAN_REPORT_NAME;
+		/// Requires REPORT_FID and REPORT_DFID_NAME
+		const REPORT_TARGET_FID = sys::FAN_REPORT_TARGET_FID;
+		/// (since Linux 6.14) Requires CLASS_NOTIF, and no file handle reporting.
+		///  Only mount namespace marks are allowed.
+		const REPORT_MNT = sys::FAN_REPORT_MNT;
+	}
 }
 impl InitFlags {
-	/// Convert the struct values to the integer representation. TODO: Rename?
-	///
-	/// TODO: Make macro if possible
-	pub fn to_bits(&self) -> i32 {
-		let mut flags = 0;
-		flags |= if self.pre_content { sys::FAN_CLASS_PRE_CONTENT } else { 0 };
-		flags |= if self.content { sys::FAN_CLASS_CONTENT } else { 0 };
-		flags |= if self.notif { sys::FAN_CLASS_NOTIF } else { 0 };
-		flags |= if self.cloexec { sys::FAN_CLOEXEC } else { 0 };
-		flags |= if self.nonblock { sys::FAN_NONBLOCK } else { 0 };
-		flags |= if self.unlimited_queue { sys::FAN_UNLIMITED_QUEUE } else { 0 };
-		flags |= if self.unlimited_marks { sys::FAN_UNLIMITED_MARKS } else { 0 };
-		flags |= if self.report_tid { sys::FAN_REPORT_TID } else { 0 };
-		flags |= if self.enable_audit { sys::FAN_ENABLE_AUDIT } else { 0 };
-		flags |= if self.report_fid { sys::FAN_REPORT_FID } else { 0 };
-		flags |= if self.report_dir_fid { sys::FAN_REPORT_DIR_FID } else { 0 };
-		//flags |= if self.report_name { sys::FAN_REPORT_NAME } else { 0 };
-		flags |= if self.report_dfid_name { sys::FAN_REPORT_DFID_NAME } else { 0 };
-		//flags |= if self.report_target_fid { sys::FAN_REPORT_TARGET_FID } else { 0 };
-		flags |= if self.report_dfid_name_target { sys::FAN_REPORT_DFID_NAME_TARGET } else { 0 };
-		flags |= if self.report_pidfd { sys::FAN_REPORT_PIDFD } else { 0 };
-		flags
+	/// Any of the flags which make the group report file handles.
+	pub const REPORT_ANY_FID: Self = Self::from_bits_retain(sys::FAN_REPORT_FID | sys::FAN_REPORT_DIR_FID);
+
+	/// Check for mutually exclusive or incomplete flag combinations.
+	pub fn validate(&self) -> Result<(), FlagError> {
+		let class = self.bits() & (sys::FAN_CLASS_CONTENT | sys::FAN_CLASS_PRE_CONTENT);
+		if class == sys::FAN_CLASS_CONTENT | sys::FAN_CLASS_PRE_CONTENT {
+			return Err(FlagError::MultipleClasses);
+		}
+		if self.contains(Self::REPORT_TID | Self::REPORT_PIDFD) {
+			return Err(FlagError::TidWithPidfd);
+		}
+		if class != sys::FAN_CLASS_NOTIF && self.intersects(Self::REPORT_ANY_FID | Self::REPORT_NAME | Self::REPORT_TARGET_FID) {
+			return Err(FlagError::FidWithContentClass);
+		}
+		if self.contains(Self::REPORT_NAME) && !self.contains(Self::REPORT_DIR_FID) {
+			return Err(FlagError::NameWithoutDirFid);
+		}
+		if self.contains(Self::REPORT_TARGET_FID) && !self.contains(Self::REPORT_DFID_NAME | Self::REPORT_FID) {
+			return Err(FlagError::TargetFidIncomplete);
+		}
+		if self.contains(Self::REPORT_MNT) && (class != sys::FAN_CLASS_NOTIF || self.intersects(Self::REPORT_ANY_FID | Self::REPORT_NAME)) {
+			return Err(FlagError::MntWithFileReporting);
+		}
+
+		Ok(())
 	}
 }
 
-/// Source: fanotify_init
-#[derive(Clone, Copy, Debug, Default)]
-pub struct EventFdFlags {
-	pub rdonly: bool,
-	pub wronly: bool,
-	pub rdwr: bool,
-	pub largefile: bool,
-	pub cloexec: bool,
-	pub append: bool,
-	pub dsync: bool,
-	pub noatime: bool,
-	pub nonblock: bool,
-	pub sync: bool
+bitflags! {
+	/// Flags for descriptors given with events. Same as `open()` flags.
+	///
+	/// Source: fanotify_init
+	#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+	pub struct EventFdFlags: i32 {
+		const RDONLY = sys::O_RDONLY;
+		const WRONLY = sys::O_WRONLY;
+		const RDWR = sys::O_RDWR;
+		const LARGEFILE = sys::O_LARGEFILE;
+		const CLOEXEC = sys::O_CLOEXEC;
+		const APPEND = sys::O_APPEND;
+		/// Includes dsync
+		const SYNC = sys::O_SYNC;
+		const DSYNC = sys::O_DSYNC;
+		const NOATIME = sys::O_NOATIME;
+		const NONBLOCK = sys::O_NONBLOCK;
+	}
 }
 impl EventFdFlags {
-	pub fn to_bits(&self) -> i32 {
-		let mut flags = 0;
-		flags |= if self.rdonly { sys::O_RDONLY } else { 0 };
-		flags |= if self.wronly { sys::O_WRONLY } else { 0 };
-		flags |= if self.rdwr { sys::O_RDWR } else { 0 };
-		flags |= if self.largefile { sys::O_LARGEFILE } else { 0 };
-		flags |= if self.cloexec { sys::O_CLOEXEC } else { 0 };
-		flags |= if self.append { sys::O_APPEND } else { 0 };
-		flags |= if self.dsync { sys::O_DSYNC } else { 0 };
-		flags |= if self.noatime { sys::O_NOATIME } else { 0 };
-		flags |= if self.nonblock { sys::O_NONBLOCK } else { 0 };
-		flags |= if self.sync { sys::O_SYNC } else { 0 };
-		flags
+	/// Check for mutually exclusive flag combinations.
+	pub fn validate(&self) -> Result<(), FlagError> {
+		if self.contains(Self::WRONLY | Self::RDWR) {
+			return Err(FlagError::AccessMode);
+		}
+
+		Ok(())
 	}
 }
 
@@ -98,151 +116,171 @@ impl EventFdFlags {
 /// * `Inode` - Default. Mark only a single inode.
 /// * `Mount` - Mark the entire mount. Requires `CAP_SYS_ADMIN`.
 /// * `Fs` - Mark the entire filesystem. Requires `CAP_SYS_ADMIN`.
-#[derive(Clone, Copy, Debug)]
+/// * `MntNs` - Mark a mount namespace, given by its `/proc/<pid>/ns/mnt` file.
+///   Requires a `REPORT_MNT` group, and `CAP_SYS_ADMIN` in the namespace.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
 pub enum MarkType {
+	#[default]
 	Inode,
 	Mount,
-	Fs
-}
-impl Default for MarkType {
-	fn default() -> Self {
-		MarkType::Inode
-	}
+	Fs,
+	MntNs
 }
 impl MarkType {
 	pub fn to_bits(&self) -> i32 {
 		match self {
 			MarkType::Inode => sys::FAN_MARK_INODE,
 			MarkType::Mount => sys::FAN_MARK_MOUNT,
-			MarkType::Fs => sys::FAN_MARK_FILESYSTEM
+			MarkType::Fs => sys::FAN_MARK_FILESYSTEM,
+			MarkType::MntNs => sys::FAN_MARK_MNTNS
 		}
 	}
 }
 
-/// Source: fanotify_mark
-// TODO: Convert inode/mount/filesystem to an enum, defaulting to inode
-// Types are mutually exclusive, see [https://elixir.bootlin.com/linux/v6.3.8/source/fs/notify/fanotify/fanotify_user.c#L1660]
-#[derive(Clone, Copy, Debug, Default)]
-pub struct MarkFlags {
-	pub dont_follow: bool,
-	pub onlydir: bool,
-	pub ignored_mask: bool,
-	pub ignore: bool,
-	pub ignored_surv_modify: bool,
-	pub ignore_surv: bool,
-	pub evictable: bool
+bitflags! {
+	/// Flags modifying a mark. The mark action and type are given separately.
+	///
+	/// Source: fanotify_mark
+	// Types are mutually exclusive, see [https://elixir.bootlin.com/linux/v6.3.8/source/fs/notify/fanotify/fanotify_user.c#L1660]
+	#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+	pub struct MarkFlags: i32 {
+		const DONT_FOLLOW = sys::FAN_MARK_DONT_FOLLOW;
+		const ONLYDIR = sys::FAN_MARK_ONLYDIR;
+		/// Mutually exclusive with IGNORE
+		const IGNORED_MASK = sys::FAN_MARK_IGNORED_MASK;
+		/// Synonym for (IGNORE | IGNORED_SURV_MODIFY)
+		const IGNORE_SURV = sys::FAN_MARK_IGNORE_SURV;
+		/// Mutually exclusive with IGNORED_MASK. Requires IGNORED_SURV_MODIFY on
+		///  directories, mounts, and filesystems.
+		const IGNORE = sys::FAN_MARK_IGNORE;
+		/// Requires IGNORE or IGNORED_MASK
+		const IGNORED_SURV_MODIFY = sys::FAN_MARK_IGNORED_SURV_MODIFY;
+		const EVICTABLE = sys::FAN_MARK_EVICTABLE;
+	}
 }
 impl MarkFlags {
-	pub fn to_bits(&self) -> i32 {
-		let mut flags = 0;
-		flags |= if self.dont_follow { sys::FAN_MARK_DONT_FOLLOW } else { 0 };
-		flags |= if self.onlydir { sys::FAN_MARK_ONLYDIR } else { 0 };
-		//flags |= if self.mount { sys::FAN_MARK_MOUNT } else { 0 };
-		//flags |= if self.filesystem { sys::FAN_MARK_FILESYSTEM } else { 0 };
-		flags |= if self.ignored_mask { sys::FAN_MARK_IGNORED_MASK } else { 0 };
-		flags |= if self.ignore { sys::FAN_MARK_IGNORE } else { 0 };
-		flags |= if self.ignored_surv_modify { sys::FAN_MARK_IGNORED_SURV_MODIFY } else { 0 };
-		flags |= if self.ignore_surv { sys::FAN_MARK_IGNORE_SURV } else { 0 };
-		flags |= if self.evictable { sys::FAN_MARK_EVICTABLE } else { 0 };
-		flags
-	}
-	pub fn from_bits(flags: i32) -> Self {
-		Self {
-			dont_follow: (flags & sys::FAN_MARK_DONT_FOLLOW) != 0,
-			onlydir: (flags & sys::FAN_MARK_ONLYDIR) != 0,
-			//mount: (flags & sys::FAN_MARK_MOUNT) != 0,
-			//filesystem: (flags & sys::FAN_MARK_FILESYSTEM) != 0,
-			ignored_mask: (flags & sys::FAN_MARK_IGNORED_MASK) != 0,
-			ignore: (flags & sys::FAN_MARK_IGNORE) != 0,
-			ignored_surv_modify: (flags & sys::FAN_MARK_IGNORED_SURV_MODIFY) != 0,
-			ignore_surv: (flags & sys::FAN_MARK_IGNORE_SURV) != 0,
-			evictable: (flags & sys::FAN_MARK_EVICTABLE) != 0,
+	/// Check for mutually exclusive or incomplete flag combinations.
+	pub fn validate(&self) -> Result<(), FlagError> {
+		if self.contains(Self::IGNORE | Self::IGNORED_MASK) {
+			return Err(FlagError::IgnoreConflict);
+		}
+		if self.contains(Self::IGNORED_SURV_MODIFY) && !self.intersects(Self::IGNORE | Self::IGNORED_MASK) {
+			return Err(FlagError::SurvModifyWithoutIgnore);
 		}
+
+		Ok(())
 	}
 }
 
-/// Source: fanotify_mark
-#[derive(Clone, Copy, Debug, Default)]
-pub struct EventFlags {
-	pub access: bool,
-	pub access_perm: bool,
-	pub attrib: bool, // (since linux 5.1)
-	pub close: bool, // (close_write | close_nowrite)
-	pub close_write: bool,
-	pub close_nowrite: bool,
-	pub create: bool,
-	pub delete: bool, // (since linux 5.1)
-	pub delete_self: bool, // (since linux 5.1)
-	/// Exclusive to mark mask
-	pub event_on_child: bool,
-	pub fs_error: bool, // (since linux 5.16)
-	pub modify: bool,
-	pub move_self: bool, // (since linux 5.1)
-	pub moved: bool, // (moved_from | moved_to)
-	pub moved_from: bool, // (since linux 5.1)
-	pub moved_to: bool,
-	pub ondir: bool,
-	pub open: bool,
-	pub open_exec: bool, //(since linux 5.0)
-	pub open_exec_perm: bool, // (since linux 5.0)
-	pub open_perm: bool,
-	/// Exclusive to event mask
-	pub q_overflow: bool,
-	pub rename: bool, // (since linux 5.17)
+bitflags! {
+	/// Events to mark, or the events which occurred.
+	///
+	/// Source: fanotify_mark
+	#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+	pub struct EventFlags: i32 {
+		const ACCESS = sys::FAN_ACCESS;
+		const ACCESS_PERM = sys::FAN_ACCESS_PERM;
+		/// (since Linux 5.1)
+		const ATTRIB = sys::FAN_ATTRIB;
+		/// (CLOSE_WRITE | CLOSE_NOWRITE)
+		const CLOSE = sys::FAN_CLOSE;
+		const CLOSE_WRITE = sys::FAN_CLOSE_WRITE;
+		const CLOSE_NOWRITE = sys::FAN_CLOSE_NOWRITE;
+		const CREATE = sys::FAN_CREATE;
+		/// (since Linux 5.1)
+		const DELETE = sys::FAN_DELETE;
+		/// (since Linux 5.1)
+		const DELETE_SELF = sys::FAN_DELETE_SELF;
+		/// Exclusive to mark mask
+		const EVENT_ON_CHILD = sys::FAN_EVENT_ON_CHILD;
+		/// (since Linux 5.16)
+		const FS_ERROR = sys::FAN_FS_ERROR;
+		const MODIFY = sys::FAN_MODIFY;
+		/// (since Linux 5.1)
+		const MOVE_SELF = sys::FAN_MOVE_SELF;
+		/// (MOVED_FROM | MOVED_TO)
+		const MOVE = sys::FAN_MOVE;
+		/// (since Linux 5.1)
+		const MOVED_FROM = sys::FAN_MOVED_FROM;
+		const MOVED_TO = sys::FAN_MOVED_TO;
+		const ONDIR = sys::FAN_ONDIR;
+		const OPEN = sys::FAN_OPEN;
+		/// (since Linux 5.0)
+		const OPEN_EXEC = sys::FAN_OPEN_EXEC;
+		/// (since Linux 5.0)
+		const OPEN_EXEC_PERM = sys::FAN_OPEN_EXEC_PERM;
+		const OPEN_PERM = sys::FAN_OPEN_PERM;
+		/// Exclusive to event mask
+		const Q_OVERFLOW = sys::FAN_Q_OVERFLOW;
+		/// (since Linux 5.17)
+		const RENAME = sys::FAN_RENAME;
+		/// (since Linux 6.14) Requires a mount namespace mark
+		const MNT_ATTACH = sys::FAN_MNT_ATTACH;
+		/// (since Linux 6.14) Requires a mount namespace mark
+		const MNT_DETACH = sys::FAN_MNT_DETACH;
+	}
 }
 impl EventFlags {
-	pub fn to_bits(&self) -> i32 {
-		let mut flags = 0;
-		flags |= if self.access { sys::FAN_ACCESS } else { 0 };
-		flags |= if self.access_perm { sys::FAN_ACCESS_PERM } else { 0 };
-		flags |= if self.attrib { sys::FAN_ATTRIB } else { 0 };
-		flags |= if self.close { sys::FAN_CLOSE } else { 0 };
-		flags |= if self.close_write { sys::FAN_CLOSE_WRITE } else { 0 };
-		flags |= if self.close_nowrite { sys::FAN_CLOSE_NOWRITE } else { 0 };
-		flags |= if self.create { sys::FAN_CREATE } else { 0 };
-		flags |= if self.delete { sys::FAN_DELETE } else { 0 };
-		flags |= if self.delete_self { sys::FAN_DELETE_SELF } else { 0 };
-		flags |= if self.event_on_child { sys::FAN_EVENT_ON_CHILD } else { 0 };
-		flags |= if self.fs_error { sys::FAN_FS_ERROR } else { 0 };
-		flags |= if self.modify { sys::FAN_MODIFY } else { 0 };
-		flags |= if self.move_self { sys::FAN_MOVE_SELF } else { 0 };
-		flags |= if self.moved { sys::FAN_MOVE } else { 0 };
-		flags |= if self.moved_from { sys::FAN_MOVED_FROM } else { 0 };
-		flags |= if self.moved_to { sys::FAN_MOVED_TO } else { 0 };
-		flags |= if self.ondir { sys::FAN_ONDIR } else { 0 };
-		flags |= if self.open { sys::FAN_OPEN } else { 0 };
-		flags |= if self.open_exec { sys::FAN_OPEN_EXEC } else { 0 };
-		flags |= if self.open_exec_perm { sys::FAN_OPEN_EXEC_PERM } else { 0 };
-		flags |= if self.open_perm { sys::FAN_OPEN_PERM } else { 0 };
-		flags |= if self.q_overflow { sys::FAN_Q_OVERFLOW } else { 0 };
-		flags |= if self.rename { sys::FAN_RENAME } else { 0 };
-		flags
+	/// Events which require a response.
+	pub const PERM: Self = Self::from_bits_retain(sys::FAN_OPEN_PERM | sys::FAN_ACCESS_PERM | sys::FAN_OPEN_EXEC_PERM);
+	/// Events reported for mount namespace marks.
+	pub const MNT: Self = Self::from_bits_retain(sys::FAN_MNT_ATTACH | sys::FAN_MNT_DETACH);
+	/// Events on open files, which are all that groups not reporting file
+	///  handles, and mount marks, allow (besides `ONDIR` and `EVENT_ON_CHILD`).
+	pub const FD: Self = Self::from_bits_retain(
+		sys::FAN_ACCESS | sys::FAN_MODIFY | sys::FAN_CLOSE | sys::FAN_OPEN | sys::FAN_OPEN_EXEC | Self::PERM.bits()
+	);
+}
+
+/// Invalid flag combinations, detected before calling into the kernel.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FlagError {
+	/// More than one notification class was given.
+	MultipleClasses,
+	/// `REPORT_TID` was given with `REPORT_PIDFD`.
+	TidWithPidfd,
+	/// File handle reporting was given with a content or pre-content class.
+	FidWithContentClass,
+	/// `REPORT_NAME` was given without `REPORT_DIR_FID`.
+	NameWithoutDirFid,
+	/// `REPORT_TARGET_FID` was given without `REPORT_FID` and `REPORT_DFID_NAME`.
+	TargetFidIncomplete,
+	/// Both `WRONLY` and `RDWR` were given for event descriptors.
+	AccessMode,
+	/// Mark `IGNORE` was given with `IGNORED_MASK`.
+	IgnoreConflict,
+	/// Mark `IGNORED_SURV_MODIFY` was given without an ignore flag.
+	SurvModifyWithoutIgnore,
+	/// Mark `IGNORE` was given for a mount or filesystem without
+	///  `IGNORED_SURV_MODIFY`.
+	IgnoreWithoutSurvModify,
+	/// `REPORT_MNT` was given with file handle reporting or a content class.
+	MntWithFileReporting,
+	/// A mount namespace mark was used without `REPORT_MNT`, or another mark
+	///  type or event with it.
+	MntNsMark,
+}
+impl fmt::Display for FlagError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let msg = match self {
+			FlagError::MultipleClasses => "Only one of CLASS_NOTIF, CLASS_CONTENT, or CLASS_PRE_CONTENT may be given",
+			FlagError::TidWithPidfd => "REPORT_TID and REPORT_PIDFD are mutually exclusive",
+			FlagError::FidWithContentClass => "File handle reporting requires CLASS_NOTIF",
+			FlagError::NameWithoutDirFid => "REPORT_NAME requires REPORT_DIR_FID",
+			FlagError::TargetFidIncomplete => "REPORT_TARGET_FID requires REPORT_FID and REPORT_DFID_NAME",
+			FlagError::AccessMode => "WRONLY and RDWR are mutually exclusive",
+			FlagError::IgnoreConflict => "IGNORE and IGNORED_MASK are mutually exclusive",
+			FlagError::SurvModifyWithoutIgnore => "IGNORED_SURV_MODIFY requires IGNORE or IGNORED_MASK",
+			FlagError::IgnoreWithoutSurvModify => "IGNORE on mounts and filesystems requires IGNORED_SURV_MODIFY",
+			FlagError::MntWithFileReporting => "REPORT_MNT requires CLASS_NOTIF without file handle reporting",
+			FlagError::MntNsMark => "REPORT_MNT groups only allow mount namespace marks, for MNT_ATTACH and MNT_DETACH",
+		};
+		write!(f, "{msg}")
 	}
-	pub fn from_bits(flags: i32) -> Self {
-		Self {
-			access: (flags & sys::FAN_ACCESS) != 0,
-			access_perm: (flags & sys::FAN_ACCESS_PERM) != 0,
-			attrib: (flags & sys::FAN_ATTRIB) != 0,
-			close: (flags & sys::FAN_CLOSE) != 0,
-			close_write: (flags & sys::FAN_CLOSE_WRITE) != 0,
-			close_nowrite: (flags & sys::FAN_CLOSE_NOWRITE) != 0,
-			create: (flags & sys::FAN_CREATE) != 0,
-			delete: (flags & sys::FAN_DELETE) != 0,
-			delete_self: (flags & sys::FAN_DELETE_SELF) != 0,
-			event_on_child: (flags & sys::FAN_EVENT_ON_CHILD) != 0,
-			fs_error: (flags & sys::FAN_FS_ERROR) != 0,
-			modify: (flags & sys::FAN_MODIFY) != 0,
-			move_self: (flags & sys::FAN_MOVE_SELF) != 0,
-			moved: (flags & sys::FAN_MOVE) != 0,
-			moved_from: (flags & sys::FAN_MOVED_FROM) != 0,
-			moved_to: (flags & sys::FAN_MOVED_TO) != 0,
-			ondir: (flags & sys::FAN_ONDIR) != 0,
-			open: (flags & sys::FAN_OPEN) != 0,
-			open_exec: (flags & sys::FAN_OPEN_EXEC) != 0,
-			open_exec_perm: (flags & sys::FAN_OPEN_EXEC_PERM) != 0,
-			open_perm: (flags & sys::FAN_OPEN_PERM) != 0,
-			q_overflow: (flags & sys::FAN_Q_OVERFLOW) != 0,
-			rename: (flags & sys::FAN_RENAME) != 0,
-		}
+}
+impl std::error::Error for FlagError {}
+impl From<FlagError> for io::Error {
+	fn from(err: FlagError) -> Self {
+		io::Error::new(io::ErrorKind::InvalidInput, err)
 	}
 }
@@ -0,0 +1,121 @@
+//! Resolution of [FileHandle]s, as reported by groups using FID reporting.
+//!
+//! File handles are only meaningful relative to the filesystem they came from,
+//!  so opening one requires a descriptor for any object on that filesystem.
+//!  [MountRegistry] keeps such a descriptor per filesystem ID.
+//!
+//! Opening handles requires `CAP_DAC_READ_SEARCH`.
+
+use crate::{sys, FileHandle, Result};
+
+use std::collections::HashMap;
+use std::{fs, io, mem};
+use std::os::fd::{AsFd, AsRawFd, BorrowedFd, FromRawFd};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::{Path, PathBuf};
+
+impl FileHandle {
+	/// Open the object referred to by this handle, using `open_by_handle_at()`.
+	///
+	/// `mount_fd` may be any descriptor on the same filesystem as the handle.
+	///  `flags` are the usual `open()` flags (e.g. [sys::O_RDONLY]).
+	pub fn open<F: AsFd>(&self, mount_fd: F, flags: i32) -> Result<fs::File> {
+		// Kernel expects the handle bytes inline, directly following the header.
+		let mut buf = Vec::with_capacity(mem::size_of::<sys::file_handle>() + self.handle.len());
+		buf.extend_from_slice(&(self.handle.len() as u32).to_ne_bytes());
+		buf.extend_from_slice(&self.fh.handle_type.to_ne_bytes());
+		buf.extend_from_slice(&self.handle);
+
+		let fd = unsafe {
+			sys::open_by_handle_at(mount_fd.as_fd().as_raw_fd(), buf.as_ptr() as *const sys::file_handle, flags)
+		};
+		let err = io::Error::last_os_error();
+
+		if fd == -1 {
+			return Err(err);
+		}
+
+		// SAFETY: Descriptor is valid and owned after successful return.
+		Ok(unsafe { fs::File::from_raw_fd(fd) })
+	}
+
+	/// Get the current path of the object referred to by this handle.
+	///
+	/// See [fd_path] for the format of the returned path.
+	pub fn path<F: AsFd>(&self, mount_fd: F) -> Result<PathBuf> {
+		let file = self.open(mount_fd, sys::O_PATH | sys::O_CLOEXEC)?;
+		fd_path(file)
+	}
+}
+
+/// Get the path a descriptor refers to, by way of `/proc/self/fd`.
+///
+/// The path is as seen from this process' mount namespace. If the object has
+///  since been deleted, the kernel appends " (deleted)" to the path.
+pub fn fd_path<F: AsFd>(fd: F) -> Result<PathBuf> {
+	fs::read_link(format!("/proc/self/fd/{}", fd.as_fd().as_raw_fd()))
+}
+
+/// Get the filesystem ID for the filesystem `fd` resides on.
+pub fn fsid_of<F: AsFd>(fd: F) -> Result<sys::fsid_t> {
+	let mut buf = mem::MaybeUninit::<sys::statfs>::uninit();
+	let res = unsafe { sys::fstatfs(fd.as_fd().as_raw_fd(), buf.as_mut_ptr()) };
+	let err = io::Error::last_os_error();
+
+	if res == -1 {
+		return Err(err);
+	}
+
+	// SAFETY: Struct filled by successful call.
+	Ok(unsafe { buf.assume_init() }.f_fsid)
+}
+
+/// Maps filesystem IDs to descriptors usable for opening [FileHandle]s.
+#[derive(Debug, Default)]
+pub struct MountRegistry {
+	mounts: HashMap<sys::fsid_t, fs::File>,
+}
+impl MountRegistry {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Register the filesystem containing `path`, returning its ID.
+	///
+	/// Does nothing (besides returning the ID) if the filesystem is already
+	///  registered.
+	pub fn insert<P: AsRef<Path>>(&mut self, path: P) -> Result<sys::fsid_t> {
+		// `open_by_handle_at()` rejects `O_PATH` descriptors for the mount.
+		let file = fs::OpenOptions::new()
+			.read(true)
+			.custom_flags(sys::O_CLOEXEC)
+			.open(path)?;
+		let fsid = fsid_of(&file)?;
+		self.mounts.entry(fsid).or_insert(file);
+
+		Ok(fsid)
+	}
+
+	/// Get the descriptor registered for `fsid`.
+	pub fn get(&self, fsid: &sys::fsid_t) -> Option<BorrowedFd<'_>> {
+		self.mounts.get(fsid).map(|f| f.as_fd())
+	}
+
+	/// Open `fh` using the descriptor registered for its filesystem.
+	///
+	/// Fails with [io::ErrorKind::NotFound] if the filesystem is not registered.
+	pub fn open(&self, fh: &FileHandle, flags: i32) -> Result<fs::File> {
+		match self.get(&fh.fsid) {
+			Some(mount_fd) => fh.open(mount_fd, flags),
+			None => Err(io::Error::new(io::ErrorKind::NotFound, "No mount registered for file handle fsid.")),
+		}
+	}
+
+	/// Open `fh` and resolve its current path.
+	pub fn resolve(&self, fh: &FileHandle, flags: i32) -> Result<(fs::File, PathBuf)> {
+		let file = self.open(fh, flags)?;
+		let path = fd_path(&file)?;
+
+		Ok((file, path))
+	}
+}
@@ -10,58 +10,382 @@
 
 pub mod sys;
 pub mod flags;
+pub mod coalesce;
+#[cfg(feature = "fake")]
+pub mod fake;
+pub mod handle;
+pub mod proc;
+#[cfg(feature = "tokio")]
+pub mod stream;
 
 use std::convert::TryFrom;
-use std::{ffi, fs, mem};
-use std::io::{self, Read};
+use std::{ffi, fmt, fs, mem};
+use std::io::{self, Read, Write};
 use std::ops::Range;
 use std::os::fd::{self, AsFd, AsRawFd, FromRawFd, IntoRawFd};
-use std::path::Path;
+use std::os::unix::ffi::OsStrExt;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use flags::*;
+use handle::MountRegistry;
 
 type Result<T> = std::result::Result<T, io::Error>;
 
+/// Size of the buffer events are read into.
+const BUF_SIZE: usize = 4096;
+
 /// Represents a single event returned through fanotify.
 ///
 /// TODO: Figure out how to fit all event types in this, or expand with an enum
 ///  or similar.
-/// TODO: Needs to handle directory events (and those with files associated).
 const EVT_META_SIZE: usize = mem::size_of::<sys::event_metadata>();
 #[derive(Debug)]
 pub struct Event {
 	pub mask: EventFlags,
 	pub file: EventFile,
-	pub pid: u32
+	/// ID of the process (or thread, see [Event::pid_is_tid]) causing the event.
+	pub pid: u32,
+	/// Additional info records that followed the event metadata.
+	pub info: Vec<Info>,
+	/// Whether this is a permission event not yet responded to.
+	response_pending: bool,
+	/// Group to allow a pending event through if dropped, if read from one.
+	group: Option<fs::File>,
+	/// Whether `pid` is a thread ID (`REPORT_TID`).
+	tid: bool,
+}
+impl Event {
+	/// Whether [Event::pid] is a thread ID rather than a process ID.
+	pub fn pid_is_tid(&self) -> bool {
+		self.tid
+	}
+
+	/// Descriptor for the process which caused the event.
+	///
+	/// Only available if the group was initialized with `REPORT_PIDFD`, and the
+	///  process had not exited (see [Info::PidFd]). Unlike the PID, the
+	///  descriptor cannot be recycled for another process.
+	pub fn pidfd(&self) -> Option<fd::BorrowedFd<'_>> {
+		self.info.iter().find_map(|i| match i {
+			Info::PidFd(PidFd::Fd(fd)) => Some(fd.as_fd()),
+			_ => None,
+		})
+	}
+
+	/// Take ownership of the process descriptor, if any. See [Event::pidfd].
+	pub fn take_pidfd(&mut self) -> Option<fd::OwnedFd> {
+		self.info.iter_mut().find_map(|i| match i {
+			Info::PidFd(pidfd @ PidFd::Fd(_)) => match mem::replace(pidfd, PidFd::Taken) {
+				PidFd::Fd(fd) => Some(fd),
+				_ => None,
+			},
+			_ => None,
+		})
+	}
+
+	/// Whether a response must still be given using [Fanotify::respond].
+	///
+	/// The accessing process stays blocked until a response is written, so an
+	///  event read from a group is allowed if dropped without one. Events from
+	///  an [EventDecoder] have no group, so stay pending until it is closed.
+	pub fn needs_response(&self) -> bool {
+		self.response_pending
+	}
+
+	/// Error details of a `FAN_FS_ERROR` event.
+	pub fn fs_error(&self) -> Option<FsError> {
+		if !self.mask.contains(EventFlags::FS_ERROR) {
+			return None;
+		}
+
+		let (errno, count) = self.info.iter().find_map(|i| match i {
+			Info::Error(errno, count) => Some((*errno as i32, *count)),
+			_ => None,
+		})?;
+		let fh = self.info.iter().find_map(|i| match i {
+			Info::Fid(fh) => Some(fh),
+			_ => None,
+		})?;
+
+		Some(FsError {
+			errno,
+			count,
+			fsid: fh.fsid,
+			// Errors not tied to an inode have an empty handle.
+			handle: (!fh.handle.is_empty()).then(|| fh.clone()),
+		})
+	}
+
+	/// ID of the mount of a `MNT_ATTACH` or `MNT_DETACH` event.
+	///
+	/// Same as `statx()` reports with `STATX_MNT_ID_UNIQUE`.
+	pub fn mnt_id(&self) -> Option<u64> {
+		self.info.iter().find_map(|i| match i {
+			Info::Mnt(id) => Some(*id),
+			_ => None,
+		})
+	}
+
+	/// Source and destination of a `FAN_RENAME` event.
+	///
+	/// Requires the group report directory handles with names (`REPORT_DFID_NAME`).
+	pub fn rename(&self) -> Option<Rename<'_>> {
+		if !self.mask.contains(EventFlags::RENAME) {
+			return None;
+		}
+
+		let (old_dir, old_name) = self.info.iter().find_map(|i| match i {
+			Info::OldDfidName(fh, name) => Some((fh, name.as_os_str())),
+			_ => None,
+		})?;
+		let (new_dir, new_name) = self.info.iter().find_map(|i| match i {
+			Info::NewDfidName(fh, name) => Some((fh, name.as_os_str())),
+			_ => None,
+		})?;
+
+		Some(Rename { old_dir, old_name, new_dir, new_name })
+	}
+}
+
+/// Filesystem error reported by a `FAN_FS_ERROR` event.
+#[derive(Clone, Debug)]
+pub struct FsError {
+	/// Error number (e.g. `EIO`, or `EUCLEAN` for corruption).
+	pub errno: i32,
+	/// Number of errors since the last error was reported, including this one.
+	pub count: u32,
+	/// Filesystem the error occurred on.
+	pub fsid: sys::fsid_t,
+	/// Object the error occurred on, if the error is specific to one.
+	pub handle: Option<FileHandle>,
+}
+impl FsError {
+	/// Convert [FsError::errno] to an [io::Error].
+	pub fn io_error(&self) -> io::Error {
+		io::Error::from_raw_os_error(self.errno)
+	}
+}
+
+/// Both sides of a rename, borrowed from the [Event] info records.
+#[derive(Clone, Copy, Debug)]
+pub struct Rename<'a> {
+	/// Directory the entry was moved from.
+	pub old_dir: &'a FileHandle,
+	/// Name of the entry before the rename.
+	pub old_name: &'a ffi::OsStr,
+	/// Directory the entry was moved to.
+	pub new_dir: &'a FileHandle,
+	/// Name of the entry after the rename.
+	pub new_name: &'a ffi::OsStr,
 }
 
 /// Should represent the various "file" references that fanotify returns.
 #[derive(Debug)]
 pub enum EventFile {
 	Fd(fs::File),
-	Fh(FileHandle) // Unable to be used for permission responses (PRE_CONTENT or CONTENT)
+	Fh(FileHandle), // Unable to be used for permission responses (PRE_CONTENT or CONTENT)
+	/// No file reference given (e.g. queue overflow).
+	Empty,
 }
 
 /// Represents a file handle returned by fanotify.
-#[derive(Debug)]
+///
+/// Owns a copy of the opaque handle bytes (as from `name_to_handle_at`), along
+///  with the filesystem ID the handle belongs to.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub struct FileHandle {
+	fsid: sys::fsid_t,
 	fh: sys::file_handle,
 	handle: Vec<u8>
 }
+impl FileHandle {
+	/// Create a handle from its parts, e.g. as from `name_to_handle_at()`.
+	pub fn new(fsid: sys::fsid_t, handle_type: i32, handle: &[u8]) -> Self {
+		Self {
+			fsid,
+			fh: sys::file_handle {
+				handle_bytes: handle.len() as u32,
+				handle_type,
+				handle: [],
+			},
+			handle: handle.to_vec(),
+		}
+	}
+
+	/// Filesystem ID of the filesystem containing the object.
+	pub fn fsid(&self) -> sys::fsid_t {
+		self.fsid
+	}
+	/// Handle type, as used by `open_by_handle_at`.
+	pub fn handle_type(&self) -> i32 {
+		self.fh.handle_type
+	}
+	/// Opaque handle bytes.
+	pub fn bytes(&self) -> &[u8] {
+		&self.handle
+	}
+}
+
+/// Process descriptor reported with an event.
+#[derive(Debug)]
+pub enum PidFd {
+	/// Descriptor referring to the process. Closed on drop.
+	Fd(fd::OwnedFd),
+	/// Process exited before the descriptor could be created.
+	Exited,
+	/// Kernel failed to create the descriptor.
+	Error,
+	/// Descriptor was removed with [Event::take_pidfd].
+	Taken,
+}
+
+/// Response to a permission event.
+///
+/// The audit variants require the group be initialized with `ENABLE_AUDIT`.
+#[derive(Clone, Copy, Debug)]
+pub enum Response {
+	Allow,
+	Deny,
+	/// Allow, and log the decision to the audit subsystem.
+	AllowAudit,
+	/// Deny, and log the decision to the audit subsystem.
+	DenyAudit,
+	/// Deny, and log the decision along with the rule that triggered it.
+	DenyAuditRule(AuditRule),
+}
+
+/// Extra information logged with an audited response.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AuditRule {
+	pub rule_number: u32,
+	pub subj_trust: u32,
+	pub obj_trust: u32,
+}
 
 /// Should represent the various extra info that can be returned.
+#[derive(Debug)]
 pub enum Info {
-	Fid(sys::fsid_t, Vec<u8>),
-	Dfid(sys::fsid_t, Vec<u8>),
-	DfidName(sys::fsid_t, Vec<u8>, ffi::OsString),
-	PidFd(fd::RawFd),
-	Error(u32, u32)
+	/// Identifies the object the event occurred on.
+	Fid(FileHandle),
+	/// Identifies the directory the event occurred in.
+	Dfid(FileHandle),
+	/// Identifies the directory, and the name of the entry within it.
+	DfidName(FileHandle, ffi::OsString),
+	/// Directory and entry name a rename moved from.
+	OldDfidName(FileHandle, ffi::OsString),
+	/// Directory and entry name a rename moved to.
+	NewDfidName(FileHandle, ffi::OsString),
+	/// Descriptor for the process which caused the event (`REPORT_PIDFD`).
+	PidFd(PidFd),
+	/// Error number, and count of errors since last report.
+	Error(u32, u32),
+	/// Unique ID of the mount attached or detached (`REPORT_MNT`).
+	Mnt(u64),
+}
+impl Info {
+	/// Parse the info record at the start of `buf`.
+	///
+	/// Returns the info (None if type unrecognized) and the full record length,
+	///  or None if the record is malformed.
+	fn parse(buf: &[u8]) -> Option<(Option<Self>, usize)> {
+		const HDR_SIZE: usize = mem::size_of::<sys::event_info_header>();
+		const FID_SIZE: usize = mem::size_of::<sys::event_info_fid>();
+
+		if buf.len() < HDR_SIZE {
+			return None;
+		}
+
+		// SAFETY: Length checked above. Unaligned read required since packed array.
+		let hdr = unsafe { (buf.as_ptr() as *const sys::event_info_header).read_unaligned() };
+		let len = hdr.len as usize;
+		if len < HDR_SIZE || len > buf.len() {
+			return None;
+		}
+		let record = &buf[..len];
+
+		let info = match InfoType::try_from(hdr.info_type as i32) {
+			Ok(info_type @ (InfoType::Fid | InfoType::Dfid | InfoType::DfidName | InfoType::OldDfidName | InfoType::NewDfidName)) => {
+				if len < FID_SIZE {
+					return None;
+				}
+
+				// SAFETY: Length checked above.
+				let fid = unsafe { (record.as_ptr() as *const sys::event_info_fid).read_unaligned() };
+				let handle_end = FID_SIZE + fid.file_handle.handle_bytes as usize;
+				if handle_end > len {
+					return None;
+				}
+
+				let fh = FileHandle {
+					fsid: fid.fsid,
+					fh: fid.file_handle,
+					handle: record[FID_SIZE..handle_end].to_vec(),
+				};
+
+				if info_type == InfoType::Fid {
+					Some(Info::Fid(fh))
+				} else if info_type == InfoType::Dfid {
+					Some(Info::Dfid(fh))
+				} else {
+					// Name is null-terminated, followed by padding up to record length.
+					let name = ffi::CStr::from_bytes_until_nul(&record[handle_end..]).ok()?;
+					let name = ffi::OsStr::from_bytes(name.to_bytes()).to_owned();
+					match info_type {
+						InfoType::OldDfidName => Some(Info::OldDfidName(fh, name)),
+						InfoType::NewDfidName => Some(Info::NewDfidName(fh, name)),
+						_ => Some(Info::DfidName(fh, name)),
+					}
+				}
+			},
+			Ok(InfoType::PidFd) => {
+				if len < mem::size_of::<sys::event_info_pidfd>() {
+					return None;
+				}
+
+				// SAFETY: Length checked above.
+				let info = unsafe { (record.as_ptr() as *const sys::event_info_pidfd).read_unaligned() };
+				let pidfd = match info.pidfd {
+					// SAFETY: Descriptor is valid, and must be closed by the reader.
+					fd if fd >= 0 => PidFd::Fd(unsafe { fd::OwnedFd::from_raw_fd(fd) }),
+					sys::FAN_NOPIDFD => PidFd::Exited,
+					_ => PidFd::Error,
+				};
+				Some(Info::PidFd(pidfd))
+			},
+			Ok(InfoType::Error) => {
+				if len < mem::size_of::<sys::event_info_error>() {
+					return None;
+				}
+
+				// SAFETY: Length checked above.
+				let info = unsafe { (record.as_ptr() as *const sys::event_info_error).read_unaligned() };
+				Some(Info::Error(info.error, info.error_count))
+			},
+			Ok(InfoType::Mnt) => {
+				if len < mem::size_of::<sys::event_info_mnt>() {
+					return None;
+				}
+
+				// SAFETY: Length checked above.
+				let info = unsafe { (record.as_ptr() as *const sys::event_info_mnt).read_unaligned() };
+				Some(Info::Mnt(info.mnt_id))
+			},
+			Err(_) => None,
+		};
+
+		Some((info, len))
+	}
 }
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum InfoType {
 	Fid,
 	Dfid,
 	DfidName,
 	PidFd,
 	Error,
+	OldDfidName,
+	NewDfidName,
+	Mnt,
 }
 impl TryFrom<i32> for InfoType {
 	type Error = ();
@@ -73,11 +397,277 @@ impl TryFrom<i32> for InfoType {
 			sys::FAN_EVENT_INFO_TYPE_DFID => Ok(Self::Dfid),
 			sys::FAN_EVENT_INFO_TYPE_PIDFD => Ok(Self::PidFd),
 			sys::FAN_EVENT_INFO_TYPE_ERROR => Ok(Self::Error),
+			sys::FAN_EVENT_INFO_TYPE_MNT => Ok(Self::Mnt),
+			sys::FAN_EVENT_INFO_TYPE_OLD_DFID_NAME => Ok(Self::OldDfidName),
+			sys::FAN_EVENT_INFO_TYPE_NEW_DFID_NAME => Ok(Self::NewDfidName),
 			_ =>Err(())
 		}
 	}
 }
 
+/// Errors from decoding events read from the group.
+///
+/// Returned within an [io::Error], retrievable with [EventError::from_io].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EventError {
+	/// Event was shorter than its metadata, or extended past the data read.
+	Truncated,
+	/// Metadata version is not [sys::FANOTIFY_METADATA_VERSION].
+	Version(u8),
+	/// The kernel event queue overflowed, so events were lost.
+	Overflow,
+}
+impl EventError {
+	/// The [io::ErrorKind] used when converting to an [io::Error].
+	pub fn kind(&self) -> io::ErrorKind {
+		match self {
+			EventError::Truncated => io::ErrorKind::UnexpectedEof,
+			EventError::Version(_) => io::ErrorKind::InvalidData,
+			EventError::Overflow => io::ErrorKind::Other,
+		}
+	}
+
+	/// Get the decoding error contained in `err`, if any.
+	pub fn from_io(err: &io::Error) -> Option<Self> {
+		err.get_ref()?.downcast_ref::<Self>().copied()
+	}
+}
+impl fmt::Display for EventError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			EventError::Truncated => write!(f, "Truncated fanotify event"),
+			EventError::Version(v) => write!(f, "Unsupported fanotify metadata version {v}"),
+			EventError::Overflow => write!(f, "Fanotify event queue overflowed"),
+		}
+	}
+}
+impl std::error::Error for EventError {}
+impl From<EventError> for io::Error {
+	fn from(err: EventError) -> Self {
+		io::Error::new(err.kind(), err)
+	}
+}
+
+impl Drop for Event {
+	fn drop(&mut self) {
+		if let (true, Some(group), EventFile::Fd(file)) = (self.response_pending, &self.group, &self.file) {
+			// Nothing to report a failure to, and the access stays blocked regardless.
+			let _ = write_response(group, file, Response::Allow);
+		}
+	}
+}
+
+/// Decodes events from data as read from a fanotify group.
+///
+/// Allows events to be parsed from any buffer, such as one captured earlier,
+///  or built with `fake::FakeEvent`. Iteration ends at the end of the buffer.
+///  As with [Fanotify::events], the event following a decoding error is
+///  returned next, unless the error leaves the rest of the buffer untrusted.
+///
+/// The descriptor of an event which cannot be decoded is closed. Since there
+///  is no group to respond to, a permission event among them stays pending.
+#[derive(Debug)]
+pub struct EventDecoder<'a> {
+	buf: &'a [u8],
+	flags: InitFlags,
+}
+impl<'a> EventDecoder<'a> {
+	/// Decode `buf`, as read from a group initialized with `flags`.
+	///
+	/// # Safety
+	/// Event and pidfd descriptors in `buf` are owned (and closed) by the
+	///  decoded events, so must be open and not owned by anything else.
+	pub unsafe fn new(buf: &'a [u8], flags: InitFlags) -> Self {
+		Self { buf, flags }
+	}
+
+	/// Data not yet decoded.
+	pub fn remaining(&self) -> &'a [u8] {
+		self.buf
+	}
+}
+impl Iterator for EventDecoder<'_> {
+	type Item = Result<Event>;
+
+	fn next(&mut self) -> Option<Self::Item> {
+		if self.buf.is_empty() {
+			return None;
+		}
+
+		// SAFETY: Descriptor ownership guaranteed by caller of `new()`.
+		let (len, res) = unsafe { decode(self.buf, &self.flags, None) };
+		self.buf = &self.buf[len..];
+
+		Some(res)
+	}
+}
+
+/// Decode the event at the start of `buf`, which must not be empty.
+///
+/// Returns the number of bytes consumed, which is the rest of the buffer if
+///  nothing after the error can be trusted.
+///
+/// If the event cannot be decoded, its descriptor is closed, and a permission
+///  event is allowed through `group` (if given), same as if the group closed.
+///
+/// # Safety
+/// See [EventDecoder::new].
+unsafe fn decode(buf: &[u8], flags: &InitFlags, group: Option<&fs::File>) -> (usize, Result<Event>) {
+	if buf.len() < EVT_META_SIZE {
+		return (buf.len(), Err(EventError::Truncated.into()));
+	}
+
+	// SAFETY: Length checked above. Unaligned read required since packed array.
+	let evt = &(buf.as_ptr() as *const sys::event_metadata).read_unaligned();
+
+	// Release the event before reporting an error, else the descriptor leaks
+	//  and a permission event blocks the accessing process until the group
+	//  closes. Assumes the fixed fields keep their layout across versions.
+	let discard = |err: EventError| -> Result<Event> {
+		if evt.fd >= 0 {
+			// SAFETY: Descriptor ownership guaranteed by caller.
+			let file = fs::File::from_raw_fd(evt.fd);
+			if let (Some(group), true) = (group, evt.mask & EventFlags::PERM.bits() as u64 != 0) {
+				// Nothing better to do if this fails, so the decoding error is returned.
+				let _ = write_response(group, &file, Response::Allow);
+			}
+		}
+
+		Err(err.into())
+	};
+
+	// If metadata version mismatch, nothing else in the buffer can be trusted.
+	if evt.vers != sys::FANOTIFY_METADATA_VERSION as u8 {
+		return (buf.len(), discard(EventError::Version(evt.vers)));
+	}
+
+	// If event (somehow) extends beyond buffer length, or is shorter than the
+	//  metadata, the event boundaries cannot be trusted.
+	let event_len = evt.event_len as usize;
+	if event_len > buf.len() || event_len < EVT_META_SIZE {
+		return (buf.len(), discard(EventError::Truncated));
+	}
+
+	// Parse any additional info records following the metadata.
+	let mut info = Vec::new();
+	let mut info_remain = &buf[EVT_META_SIZE..event_len];
+	while !info_remain.is_empty() {
+		// Stop at the first malformed record, since nothing after can be trusted.
+		let Some((record, len)) = Info::parse(info_remain) else {
+			break;
+		};
+		if let Some(record) = record {
+			info.push(record);
+		}
+
+		// Move info slice forward by current length
+		info_remain = &info_remain[len..];
+	}
+
+	if (evt.mask & sys::FAN_Q_OVERFLOW as u64) != 0 {
+		return (event_len, Err(EventError::Overflow.into()));
+	}
+
+	let response_pending = (evt.mask & EventFlags::PERM.bits() as u64) != 0 && evt.fd >= 0;
+	// Duplicated, so the event can be allowed if dropped (even after the group).
+	let group = group.filter(|_| response_pending).and_then(|g| g.try_clone().ok());
+
+	// Groups reporting FIDs give no descriptor, so use the handle instead.
+	let file = if evt.fd == sys::FAN_NOFD {
+		Fanotify::info_handle(&info).map_or(EventFile::Empty, |fh| EventFile::Fh(fh.clone()))
+	} else {
+		// SAFETY: Descriptor ownership guaranteed by caller.
+		EventFile::Fd(fs::File::from_raw_fd(evt.fd))
+	};
+
+	(event_len, Ok(Event {
+		mask: EventFlags::from_bits_retain(evt.mask as i32),
+		file,
+		pid: evt.pid,
+		info,
+		response_pending,
+		group,
+		tid: flags.contains(InitFlags::REPORT_TID),
+	}))
+}
+
+/// Write a response for the permission event with descriptor `file`.
+fn write_response(mut group: &fs::File, file: &fs::File, resp: Response) -> Result<()> {
+	let response = match resp {
+		Response::Allow => sys::FAN_ALLOW,
+		Response::Deny => sys::FAN_DENY,
+		Response::AllowAudit => sys::FAN_ALLOW | sys::FAN_AUDIT,
+		Response::DenyAudit => sys::FAN_DENY | sys::FAN_AUDIT,
+		Response::DenyAuditRule(_) => sys::FAN_DENY | sys::FAN_AUDIT | sys::FAN_INFO,
+	};
+	let hdr = sys::response {
+		fd: file.as_raw_fd() as u32,
+		response: response as u32,
+	};
+
+	// Response info (if any) immediately follows the response struct.
+	// SAFETY: Both structs are `repr(C)` without padding, so all bytes are initialized.
+	let mut buf = unsafe { struct_bytes(&hdr) }.to_vec();
+	if let Response::DenyAuditRule(rule) = resp {
+		let info = sys::response_info_audit_rule {
+			hdr: sys::response_info_header {
+				typ: sys::FAN_RESPONSE_INFO_AUDIT_RULE as u8,
+				pad: 0,
+				len: mem::size_of::<sys::response_info_audit_rule>() as u16,
+			},
+			rule_number: rule.rule_number,
+			subj_trust: rule.subj_trust,
+			obj_trust: rule.obj_trust,
+		};
+		buf.extend_from_slice(unsafe { struct_bytes(&info) });
+	}
+
+	group.write_all(&buf)
+}
+
+/// View the raw bytes of a struct, for writing to the fanotify descriptor.
+///
+/// # Safety
+/// `T` must not contain padding bytes.
+unsafe fn struct_bytes<T>(val: &T) -> &[u8] {
+	std::slice::from_raw_parts(val as *const T as *const u8, mem::size_of::<T>())
+}
+
+/// Builder for [Fanotify], validating flag combinations before initializing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FanotifyBuilder {
+	flags: InitFlags,
+	event_fd_flags: EventFdFlags,
+}
+impl FanotifyBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	/// Add to the flags given to `fanotify_init()`.
+	pub fn flags(mut self, flags: InitFlags) -> Self {
+		self.flags |= flags;
+		self
+	}
+
+	/// Add to the flags used for descriptors given with events.
+	pub fn event_fd_flags(mut self, flags: EventFdFlags) -> Self {
+		self.event_fd_flags |= flags;
+		self
+	}
+
+	/// Check the flags given so far, without initializing.
+	pub fn validate(&self) -> std::result::Result<(), FlagError> {
+		self.flags.validate()?;
+		self.event_fd_flags.validate()
+	}
+
+	/// Initialize the group. See [Fanotify::init].
+	pub fn build(&self) -> Result<Fanotify> {
+		Fanotify::init(&self.flags, &self.event_fd_flags)
+	}
+}
+
 /// Fanotify instance
 // `valid_buf` only exists because streaming iterator not possible. Struct
 //  cannot create a lifetime for purposes of slices/borrows.
@@ -86,23 +676,35 @@ pub struct Fanotify {
 	/// Hold the fd returned by fanotify. Converted to OwnedFd for Drop trait.
 	fan_fd: fs::File,
 	/// Buffer used when reading from `fan_fd`
-	evt_buffer: Box<[u8; 4096]>,
+	evt_buffer: Box<[u8; BUF_SIZE]>,
 	/// Valid buffer range.
 	valid_buf: Range<usize>,
+	/// Flags the group was initialized with.
+	flags: InitFlags,
+	/// Filesystems of marked paths, for resolving reported file handles.
+	mounts: MountRegistry,
+	/// Error encountered while reading a batch, reported on the next read.
+	deferred_err: Option<io::Error>,
 }
 
-
-// TODO: Create has_pending_events() using poll() (needs lib/crate/implement).
-// TODO: Add allow()/deny() or similar for responses to PERM events.
 impl Fanotify {
+	/// Start building an instance. See [FanotifyBuilder].
+	pub fn builder() -> FanotifyBuilder {
+		FanotifyBuilder::new()
+	}
+
 	/// Creates an fanotify instance with the given flags.
 	///
 	/// Passes the given flag parameters directly to `fanotify_init()`, and
 	///  if successful, returns an `Fanotify` instance for further
-	///  interactions.
+	///  interactions. Invalid flag combinations are rejected beforehand with
+	///  [io::ErrorKind::InvalidInput], containing a [FlagError].
 	pub fn init(flags: &InitFlags, event_fd_flags: &EventFdFlags) -> Result<Self> {
+		flags.validate()?;
+		event_fd_flags.validate()?;
+
 		let fid = unsafe {
-			sys::fanotify_init(flags.to_bits(), event_fd_flags.to_bits())
+			sys::fanotify_init(flags.bits(), event_fd_flags.bits())
 		};
 		let err = io::Error::last_os_error();
 
@@ -110,243 +712,358 @@ impl Fanotify {
 			return Err(err);
 		}
 
-		Ok(Self {
-			fan_fd: unsafe { fs::File::from_raw_fd(fid) },
-			evt_buffer: Box::new([0; 4096]),
+		Ok(Self::from_file(unsafe { fs::File::from_raw_fd(fid) }, flags))
+	}
+
+	/// Wrap a descriptor events are read from, e.g. a `fake::FakeSource`.
+	fn from_file(fan_fd: fs::File, flags: &InitFlags) -> Self {
+		Self {
+			fan_fd,
+			evt_buffer: Box::new([0; BUF_SIZE]),
 			valid_buf: Range { start: 0, end: 0 },
-		})
+			flags: *flags,
+			mounts: MountRegistry::new(),
+			deferred_err: None,
+		}
 	}
 
 	/// Mark a path for which notification events are desired.
 	///
-	/// Passes the given flag parameters directly to `fanotify_mark()`.
-	pub fn add_mark<P: AsRef<Path>>(&self, path: P, mtype: &MarkType, flags: &MarkFlags, mask: &EventFlags) -> Result<()> {
-		fn inner(slf: &Fanotify, path: &Path, mtype: &MarkType, flags: &MarkFlags, mask: &EventFlags) -> Result<()> {
-			if let Some(p) = path.to_str() {
-				let c_path = ffi::CString::new(p).expect("Path to str will error if null byte.");
-
-				// All bits except first three (FAN_MARK_{ADD,REMOVE,FLUSH})
-				let add_flags = (flags.to_bits() & 0x7FFFFE6C) | sys::FAN_MARK_ADD | mtype.to_bits();
-
-				// Call mark
-				let res = unsafe {
-					sys::fanotify_mark(slf.fan_fd.as_raw_fd(), add_flags, mask.to_bits() as u64, 0, c_path.as_ptr())
-				};
-
-				// If mark failed, return error
-				let err = io::Error::last_os_error();
-				if res == -1 {
-					return Err(err);
-				}
-
-				return Ok(());
-			}
-
-			// Return this error to be consistent with error types.
-			Err(io::Error::new(io::ErrorKind::InvalidInput, "Path contains invalid character(s)."))
-		}
-
-		inner(self, path.as_ref(), mtype, flags, mask)
+	/// Passes the given flag parameters directly to `fanotify_mark()`. If the
+	///  group reports file handles, the filesystem of `path` is also registered
+	///  for use with [Fanotify::resolve].
+	pub fn add_mark<P: AsRef<Path>>(&mut self, path: P, mtype: &MarkType, flags: &MarkFlags, mask: &EventFlags) -> Result<()> {
+		self.add_mark_at(None, path, mtype, flags, mask)
 	}
 
-	/// Return the next [Event], or None.
+	/// Same as [Fanotify::add_mark], with relative paths resolved from `dirfd`.
 	///
-	/// Since the behavior is that of a streaming iterator, which isn't
-	///  possible, the return permits use in a `while let` loop.
-	pub fn events(&mut self) -> Option<Event> {
-		// If slice too small (or empty), read new buffer.
-		if self.valid_buf.len() < EVT_META_SIZE {
-			debug_assert_eq!(self.valid_buf.len(), 0);
-
-			// Read contents into buffer and update length.
-			if let Ok(n) = self.fan_fd.read(&mut self.evt_buffer[..]) {
-				self.valid_buf = 0..n;
+	/// A `dirfd` of `None` resolves relative to the current working directory.
+	pub fn add_mark_at<P: AsRef<Path>>(&mut self, dirfd: Option<fd::BorrowedFd<'_>>, path: P, mtype: &MarkType, flags: &MarkFlags, mask: &EventFlags) -> Result<()> {
+		fn inner(slf: &mut Fanotify, dirfd: Option<fd::BorrowedFd<'_>>, path: &Path, mtype: &MarkType, flags: &MarkFlags, mask: &EventFlags) -> Result<()> {
+			flags.validate()?;
+			// Ignore masks on anything but a file must survive modification.
+			//  Directories are left to the kernel to reject.
+			if flags.contains(MarkFlags::IGNORE) && !flags.contains(MarkFlags::IGNORED_SURV_MODIFY) && *mtype != MarkType::Inode {
+				return Err(FlagError::IgnoreWithoutSurvModify.into());
+			}
+
+			// Mount namespace marks and events are only allowed in, and all that
+			//  is allowed in, REPORT_MNT groups.
+			let mntns_ok = if slf.flags.contains(InitFlags::REPORT_MNT) {
+				*mtype == MarkType::MntNs && EventFlags::MNT.contains(*mask)
 			} else {
-				return None
+				*mtype != MarkType::MntNs && !mask.intersects(EventFlags::MNT)
+			};
+			if !mntns_ok {
+				return Err(FlagError::MntNsMark.into());
 			}
-		}
 
-		// Get event metadata from buffer
-		// SAFETY: `valid_buf` maintains Range invariant, and range end is never greater than `evt_buffer` length.
-		let evt_start = unsafe {
-			self.evt_buffer.as_ptr().add(self.valid_buf.start) as *const sys::event_metadata
-		};
-		// SAFETY: Initialized above. Unaligned read required since packed array.
-		let evt = & unsafe { evt_start.read_unaligned() };
+			// Register beforehand, so a failure does not leave an unresolvable mark.
+			if slf.flags.intersects(InitFlags::REPORT_ANY_FID) {
+				match dirfd {
+					// Joining replaces the base if `path` is absolute, same as `openat()`.
+					Some(d) => slf.mounts.insert(Path::new(&format!("/proc/self/fd/{}", d.as_raw_fd())).join(path))?,
+					None => slf.mounts.insert(path)?,
+				};
+			}
 
-		// If event (somehow) extends beyond buffer length, empty range and return.
-		if (evt.event_len as usize) > self.valid_buf.len() {
-			self.valid_buf.start = self.valid_buf.end;
-			return None
+			let add_flags = sys::FAN_MARK_ADD | mtype.to_bits() | flags.bits();
+			slf.mark(add_flags, mask.bits() as u64, dirfd, path)
 		}
 
-		// If metadata version mismatch, panic
-		debug_assert_eq!(evt.vers, sys::FANOTIFY_METADATA_VERSION as u8);
-
-		// Slice for ease of parsing supplementary info.
-		let full_evt = &self.evt_buffer[self.valid_buf.start .. (self.valid_buf.start + evt.event_len as usize)];
+		inner(self, dirfd, path.as_ref(), mtype, flags, mask)
+	}
 
-		// If there is metadata
-		if evt.event_len as usize > EVT_META_SIZE {
-			eprintln!("Long event len: {}", evt.event_len);
+	/// Remove events in `mask` from an existing mark.
+	///
+	/// The mark is removed entirely once its mask is empty. `mtype` and
+	///  `flags` must match those used to add the mark (e.g. `ignore` to
+	///  remove from an ignore mask).
+	pub fn remove_mark<P: AsRef<Path>>(&mut self, path: P, mtype: &MarkType, flags: &MarkFlags, mask: &EventFlags) -> Result<()> {
+		self.remove_mark_at(None, path, mtype, flags, mask)
+	}
 
-			// Loop over additional info
-			let mut info_remain = &full_evt[EVT_META_SIZE as usize..]; // Start with full event to guarantee first loop
-			eprintln!("Additional info length: {}", info_remain.len());
+	/// Same as [Fanotify::remove_mark], with relative paths resolved from `dirfd`.
+	pub fn remove_mark_at<P: AsRef<Path>>(&mut self, dirfd: Option<fd::BorrowedFd<'_>>, path: P, mtype: &MarkType, flags: &MarkFlags, mask: &EventFlags) -> Result<()> {
+		flags.validate()?;
+		let remove_flags = sys::FAN_MARK_REMOVE | mtype.to_bits() | flags.bits();
+		self.mark(remove_flags, mask.bits() as u64, dirfd, path.as_ref())
+	}
 
-			// TODO: Check what maximum extra info structures is and possibly remove loop.
-			//  Since there should be info for distinct things (file, parent dir, etc.),
-			//  a loop might not make as much sense as just parsing N many times.
-			while !info_remain.is_empty() {
-				// Get common header
-				let info_hdr = unsafe {
-					&(info_remain.as_ptr() as *const sys::event_info_header).read_unaligned()
-				};
-				eprintln!("Info type {}, with len {}", info_hdr.info_type, info_hdr.len);
-
-				// Get full info struct based on header
-				// TODO: Change to normal int comparison so enum with values can be used
-				// TODO: Possibly move this into separate function, and have that return enum with values.
-				if let Ok(info_type) = InfoType::try_from(info_hdr.info_type as i32) { match info_type {
-					InfoType::Fid | InfoType::Dfid => {
-						eprintln!("\tINFO_(D)FID:");
-						let info = unsafe {
-							&*(info_remain.as_ptr() as *const sys::event_info_fid)
-						};
-						// Get handle bytes
-						let _handle: &[u8] = unsafe {
-							std::slice::from_raw_parts(info.file_handle.handle.as_ptr(), info.file_handle.handle_bytes as usize)
-						};
-
-						// Dump info for dev/debug
-						eprintln!("\t\tfsid({:?})\n\t\tfile_handle({:?})", info.fsid, info.file_handle);
-					},
-					InfoType::DfidName => {
-						eprintln!("\tINFO_(D)FID_NAME");
-						let info = unsafe {
-							&*(info_remain.as_ptr() as *const sys::event_info_fid)
-						};
-
-						// Get handle bytes
-						let _handle: &[u8] = unsafe {
-							std::slice::from_raw_parts(info.file_handle.handle.as_ptr(), info.file_handle.handle_bytes as usize)
-						};
-
-						// Filename guaranteed null-terminated by fanotify API
-						let name_ptr = unsafe {
-							(&info.file_handle.handle as *const _ as *const i8).offset(info.file_handle.handle_bytes as isize)
-						};
-						let fname = unsafe {
-							ffi::CStr::from_ptr(name_ptr)
-						};
-
-						eprintln!("\t\tfsid({:X?})\n\t\tfile_handle({:?})\n\t\tname({:X?})", info.fsid, info.file_handle, fname);
-					},
-					_ => {}
-				}} else {
-					eprintln!("\tUnrecognized info type.");
-				}
+	/// Ignore events in `mask` for `path`, even if matched by another mark.
+	///
+	/// Unless `survive_modify` is set, the ignore mask is cleared the next time
+	///  the object is modified. Requires Linux 5.19 or later (`FAN_MARK_IGNORE`).
+	///
+	/// Directories, mounts, and filesystems are never modified, so require
+	///  `survive_modify`. Mounts and filesystems without it are rejected with a
+	///  [FlagError], and directories by the kernel (with `EISDIR`).
+	pub fn add_ignore_mark<P: AsRef<Path>>(&mut self, path: P, mtype: &MarkType, mask: &EventFlags, survive_modify: bool) -> Result<()> {
+		self.add_ignore_mark_at(None, path, mtype, mask, survive_modify)
+	}
 
-				// Move info slice forward by current length
-				info_remain = &info_remain[info_hdr.len as usize..];
-			}
-		}
+	/// Same as [Fanotify::add_ignore_mark], with relative paths resolved from `dirfd`.
+	///
+	/// The ignore mask is removed with [Fanotify::remove_mark_at], given the
+	///  `IGNORE` flag.
+	pub fn add_ignore_mark_at<P: AsRef<Path>>(&mut self, dirfd: Option<fd::BorrowedFd<'_>>, path: P, mtype: &MarkType, mask: &EventFlags, survive_modify: bool) -> Result<()> {
+		let flags = if survive_modify { MarkFlags::IGNORE_SURV } else { MarkFlags::IGNORE };
+		self.add_mark_at(dirfd, path, mtype, &flags, mask)
+	}
 
-		// Event valid by this point. Move slice start to end of this event.
-		self.valid_buf.start += evt.event_len as usize;
+	/// Watch a mount namespace for mounts being attached or detached, reported
+	///  as `MNT_ATTACH` and `MNT_DETACH` events (see [Event::mnt_id]).
+	///
+	/// `ns_path` is the namespace file, e.g. `/proc/self/ns/mnt`. Requires the
+	///  group be initialized with `REPORT_MNT`.
+	pub fn add_mntns_mark<P: AsRef<Path>>(&mut self, ns_path: P) -> Result<()> {
+		self.add_mark(ns_path, &MarkType::MntNs, &MarkFlags::empty(), &EventFlags::MNT)
+	}
 
-		// Final check of FD for proper event type
-		if evt.fd == sys::FAN_NOFD || evt.fd == sys::FAN_NOPIDFD || evt.fd == sys::FAN_EPIDFD {
-			eprintln!("File handle vs descriptor not properly handled.");
-			return None;
+	/// Watch the filesystem containing `path` for errors, reported as
+	///  `FAN_FS_ERROR` events (see [Event::fs_error]).
+	///
+	/// Requires the group be initialized with `REPORT_FID`, `CAP_SYS_ADMIN`,
+	///  and Linux 5.16 or later. Only some filesystems (e.g. ext4) report errors.
+	pub fn add_fs_error_mark<P: AsRef<Path>>(&mut self, path: P) -> Result<()> {
+		if !self.flags.contains(InitFlags::REPORT_FID) {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "Filesystem error events require REPORT_FID."));
 		}
 
-		/* Return the event
-		File descriptor guaranteed valid by fanotify API.
-		*/
-		Some(Event {
-			mask: EventFlags::from_bits(evt.mask as i32),
-			file: EventFile::Fd(unsafe {
-				fs::File::from_raw_fd(evt.fd as i32)
-			}),
-			pid: evt.pid
-		})
+		self.add_mark(path, &MarkType::Fs, &MarkFlags::empty(), &EventFlags::FS_ERROR)
 	}
 
-	/// Clear all marks for mounts.
-	pub fn clear_mnt_marks(&mut self) -> Result<()> {
-		// Set flags and create valid pathname (flushing still requires pathname be valid).
-		let flags = sys::FAN_MARK_FLUSH | sys::FAN_MARK_MOUNT;
-		let root = ffi::CString::new("/").expect("String literal should not contain null bytes.");
+	/// Make the `fanotify_mark()` call.
+	///
+	/// `flags` must contain the action and mark type; any other action or
+	///  type bits in it are not filtered.
+	fn mark(&self, flags: i32, mask: u64, dirfd: Option<fd::BorrowedFd<'_>>, path: &Path) -> Result<()> {
+		let Ok(c_path) = ffi::CString::new(path.as_os_str().as_bytes()) else {
+			// Return this error to be consistent with error types.
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "Path contains invalid character(s)."));
+		};
+		let dirfd = dirfd.map_or(sys::AT_FDCWD, |d| d.as_raw_fd());
 
-		// Make call to flush
 		let res = unsafe {
-			sys::fanotify_mark(self.fan_fd.as_raw_fd(), flags, 0, 0, root.as_ptr())
+			sys::fanotify_mark(self.fan_fd.as_raw_fd(), flags, mask, dirfd, c_path.as_ptr())
 		};
-		let err = io::Error::last_os_error();
 
+		// If mark failed, return error
+		let err = io::Error::last_os_error();
 		if res == -1 {
 			return Err(err);
 		}
 
 		Ok(())
 	}
-	/// Clear all marks for filesystems.
-	pub fn clear_fs_marks(&mut self) -> Result<()> {
-		// Set flags and create valid pathname (flushing still requires pathname be valid).
-		let flags = sys::FAN_MARK_FLUSH | sys::FAN_MARK_FILESYSTEM;
-		let root = ffi::CString::new("/").expect("String literal should not contain null bytes.");
 
-		// Make call to flush
-		let res = unsafe {
-			sys::fanotify_mark(self.fan_fd.as_raw_fd(), flags, 0, 0, root.as_ptr())
-		};
-		let err = io::Error::last_os_error();
+	/// Return the next [Event], or None if no events are pending.
+	///
+	/// Since the behavior is that of a streaming iterator, which isn't
+	///  possible, the return permits use in a `while let Ok(Some(_))` loop.
+	///  None is only returned for non-blocking groups.
+	///
+	/// Errors from decoding are returned as [EventError]s (see
+	///  [EventError::from_io]), after which the next call resumes with the
+	///  following event. Any other error is from the read itself. A
+	///  permission event which cannot be decoded is allowed.
+	pub fn events(&mut self) -> Result<Option<Event>> {
+		if let Some(e) = self.deferred_err.take() {
+			return Err(e);
+		}
 
-		if res == -1 {
-			return Err(err);
+		// If buffer empty, read new events.
+		if self.valid_buf.is_empty() {
+			match self.fill_buffer() {
+				Ok(_) => {},
+				Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(None),
+				Err(e) => return Err(e),
+			}
 		}
 
-		Ok(())
+		self.next_buffered()
 	}
-	/// Clear all marks on specific files and directories.
-	pub fn clear_file_marks(&mut self) -> Result<()> {
-		// Set flags and create valid pathname (flushing still requires pathname be valid).
-		let flags = sys::FAN_MARK_FLUSH;
-		let root = ffi::CString::new("/").expect("String literal should not contain null bytes.");
 
-		// Make call to flush
-		let res = unsafe {
-			sys::fanotify_mark(self.fan_fd.as_raw_fd(), flags, 0, 0, root.as_ptr())
+	/// Check whether events are available, without blocking.
+	pub fn has_pending_events(&self) -> Result<bool> {
+		self.wait_for_events(Some(Duration::ZERO))
+	}
+
+	/// Wait until events are available, or the timeout elapses.
+	///
+	/// Returns whether events are available. A `None` timeout waits
+	///  indefinitely. Sub-millisecond timeouts are rounded up.
+	pub fn wait_for_events(&self, timeout: Option<Duration>) -> Result<bool> {
+		// Events (or errors) from a previous read take priority.
+		if !self.valid_buf.is_empty() || self.deferred_err.is_some() {
+			return Ok(true);
+		}
+
+		let timeout = match timeout {
+			Some(t) => t.as_nanos().div_ceil(1_000_000).min(i32::MAX as u128) as i32,
+			None => -1,
 		};
+		let mut pfd = sys::pollfd {
+			fd: self.fan_fd.as_raw_fd(),
+			events: sys::POLLIN,
+			revents: 0,
+		};
+
+		let res = unsafe { sys::poll(&mut pfd, 1, timeout) };
 		let err = io::Error::last_os_error();
 
 		if res == -1 {
 			return Err(err);
 		}
 
-		Ok(())
+		Ok(res > 0 && (pfd.revents & sys::POLLIN) != 0)
 	}
-	/// Clear all marks (mounts, filesystem, and specific files/dirs).
+
+	/// Return all events from a single read.
 	///
-	/// Equivalent to calling each of [clear_mnt_marks()], [clear_fs_marks()],
-	///  and [clear_file_marks()].
-	pub fn clear_all_marks(&mut self) -> Result<()> {
-		if let Err(e) = self.clear_mnt_marks() {
+	/// If events remain from a previous read, only those are returned.
+	///  Otherwise a read is performed, which blocks unless the group is
+	///  non-blocking, in which case an empty batch means no events were
+	///  pending.
+	pub fn read_batch(&mut self) -> Result<Vec<Event>> {
+		if let Some(e) = self.deferred_err.take() {
 			return Err(e);
 		}
-		if let Err(e) = self.clear_fs_marks() {
-			return Err(e);
+
+		if self.valid_buf.is_empty() {
+			match self.fill_buffer() {
+				Ok(_) => {},
+				Err(e) if e.kind() == io::ErrorKind::WouldBlock => return Ok(Vec::new()),
+				Err(e) => return Err(e),
+			}
 		}
-		if let Err(e) = self.clear_file_marks() {
-			return Err(e);
+
+		let mut batch = Vec::new();
+		loop {
+			match self.next_buffered() {
+				Ok(Some(evt)) => batch.push(evt),
+				Ok(None) => break,
+				Err(e) if batch.is_empty() => return Err(e),
+				// Report with the next call, so the events read are not lost.
+				Err(e) => {
+					self.deferred_err = Some(e);
+					break;
+				},
+			}
+		}
+
+		Ok(batch)
+	}
+
+	/// Read new events into the buffer, returning the number of bytes read.
+	///
+	/// Any events remaining in the buffer are discarded.
+	fn fill_buffer(&mut self) -> Result<usize> {
+		let n = self.fan_fd.read(&mut self.evt_buffer[..])?;
+		self.valid_buf = 0..n;
+
+		Ok(n)
+	}
+
+	/// Parse the next event already present in the buffer, if any.
+	fn next_buffered(&mut self) -> Result<Option<Event>> {
+		if self.valid_buf.is_empty() {
+			return Ok(None);
 		}
 
+		// SAFETY: Buffer holds data read from the group, so descriptors are ours to own.
+		let (len, res) = unsafe { decode(&self.evt_buffer[self.valid_buf.clone()], &self.flags, Some(&self.fan_fd)) };
+		self.valid_buf.start += len;
+
+		res.map(Some)
+	}
+
+	/// Respond to a permission event, allowing or denying the access.
+	///
+	/// Fails with [io::ErrorKind::InvalidInput] if the event does not need a
+	///  response (not a permission event, or already responded to).
+	pub fn respond(&self, evt: &mut Event, resp: Response) -> Result<()> {
+		if !evt.response_pending {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "Event does not need a response."));
+		}
+		let EventFile::Fd(ref file) = evt.file else {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "Event has no file descriptor."));
+		};
+
+		write_response(&self.fan_fd, file, resp)?;
+		evt.response_pending = false;
+		evt.group = None;
+
 		Ok(())
 	}
+
+	/// Allow the access which generated the permission event.
+	pub fn allow(&self, evt: &mut Event) -> Result<()> {
+		self.respond(evt, Response::Allow)
+	}
+
+	/// Deny the access which generated the permission event.
+	pub fn deny(&self, evt: &mut Event) -> Result<()> {
+		self.respond(evt, Response::Deny)
+	}
+
+	/// Filesystems registered while marking, for resolving file handles.
+	pub fn mounts(&self) -> &MountRegistry {
+		&self.mounts
+	}
+
+	/// Open a reported file handle, and resolve its current path.
+	///
+	/// Only handles on filesystems containing a path marked with
+	///  [Fanotify::add_mark] can be resolved.
+	pub fn resolve(&self, fh: &FileHandle, flags: i32) -> Result<(fs::File, PathBuf)> {
+		self.mounts.resolve(fh, flags)
+	}
+
+	/// Select the handle best identifying the event object from the info records.
+	///
+	/// Prefers the object itself (FID), falling back to the directory (DFID)
+	///  when the group only reports directory handles.
+	fn info_handle(info: &[Info]) -> Option<&FileHandle> {
+		info.iter()
+			.find_map(|i| match i {
+				Info::Fid(fh) => Some(fh),
+				_ => None,
+			})
+			.or_else(|| info.iter().find_map(|i| match i {
+				Info::Dfid(fh) | Info::DfidName(fh, _) | Info::NewDfidName(fh, _) => Some(fh),
+				_ => None,
+			}))
+	}
+
+	/// Clear all marks for mounts.
+	pub fn clear_mnt_marks(&mut self) -> Result<()> {
+		// Flushing still requires pathname be valid.
+		self.mark(sys::FAN_MARK_FLUSH | sys::FAN_MARK_MOUNT, 0, None, Path::new("/"))
+	}
+	/// Clear all marks for filesystems.
+	pub fn clear_fs_marks(&mut self) -> Result<()> {
+		// Flushing still requires pathname be valid.
+		self.mark(sys::FAN_MARK_FLUSH | sys::FAN_MARK_FILESYSTEM, 0, None, Path::new("/"))
+	}
+	/// Clear all marks on specific files and directories.
+	pub fn clear_file_marks(&mut self) -> Result<()> {
+		// Flushing still requires pathname be valid.
+		self.mark(sys::FAN_MARK_FLUSH, 0, None, Path::new("/"))
+	}
+	/// Clear all marks (mounts, filesystem, and specific files/dirs).
+	///
+	/// Equivalent to calling each of [clear_mnt_marks()], [clear_fs_marks()],
+	///  and [clear_file_marks()].
+	pub fn clear_all_marks(&mut self) -> Result<()> {
+		self.clear_mnt_marks()?;
+		self.clear_fs_marks()?;
+		self.clear_file_marks()
+	}
 }
 impl AsFd for Fanotify {
-	fn as_fd(&self) -> fd::BorrowedFd {
+	fn as_fd(&self) -> fd::BorrowedFd<'_> {
 		self.fan_fd.as_fd()
 	}
 }
@@ -1,51 +1,273 @@
-use fanotify::Fanotify;
-use fanotify::flags::{InitFlags, EventFdFlags, MarkFlags, EventFlags, MarkType};
-use std::default::Default;
-use std::path::Path;
+//! fanwatch - Watch paths, mounts, or filesystems for fanotify events.
+//!
+//! Prints one line per event, as text or JSON. Similar to `inotifywait -m`,
+//!  but able to cover entire mounts and filesystems.
 
-fn main() {
-	// Initialize the Fanotify instance
-	let flags = InitFlags {
-		cloexec: true,
-		//report_fid: true,
-		report_dfid_name: true,
-		..InitFlags::default()
+use fanotify::{handle, sys, Event, EventError, EventFile, Fanotify, Info};
+use fanotify::coalesce::Coalescer;
+use fanotify::flags::{EventFdFlags, EventFlags, InitFlags, MarkFlags, MarkType};
+use fanotify::proc::ProcCache;
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::time::Duration;
+
+const DEFAULT_EVENTS: &str = "create,delete,modify,close_write,moved_from,moved_to";
+/// Defaults for mount marks, which only allow events on open files.
+const DEFAULT_MOUNT_EVENTS: &str = "open,modify,close_write";
+
+const USAGE: &str = "Usage: fanwatch [OPTIONS] PATH...
+
+Options:
+  -t, --type TYPE     Mark type: inode (default), mount, fs, or mntns
+  -e, --events LIST   Comma-separated events to watch (default: create,delete,modify,close_write,moved_from,moved_to,
+                       and event_on_child for directories, open,modify,close_write for mount, or
+                       mnt_attach,mnt_detach for mntns)
+  -f, --fid           Report file handles instead of descriptors (implied by
+                       events other than access, modify, open, and close)
+  -n, --name          Report directory handles and entry names (implies --fid)
+  -j, --json          Print events as JSON, one object per line
+  -c, --count N       Exit after N events
+  -w, --window MS     Merge repeated events for the same file and process
+                       within MS milliseconds
+  -h, --help          Print this help
+
+Events are the names of the EventFlags constants (case insensitive), e.g.
+ access, open, close, rename, ondir, event_on_child. Permission events are
+ always allowed. Mount namespaces are given by their namespace file, e.g.
+ /proc/self/ns/mnt.";
+
+struct Args {
+	paths: Vec<PathBuf>,
+	mtype: MarkType,
+	mask: Option<EventFlags>,
+	fid: bool,
+	name: bool,
+	json: bool,
+	count: Option<usize>,
+	window: Duration,
+}
+
+fn parse_args() -> Result<Args, String> {
+	let mut args = Args {
+		paths: Vec::new(),
+		mtype: MarkType::Inode,
+		mask: None,
+		fid: false,
+		name: false,
+		json: false,
+		count: None,
+		window: Duration::ZERO,
+	};
+
+	let mut it = std::env::args().skip(1);
+	while let Some(arg) = it.next() {
+		match arg.as_str() {
+			"-t" | "--type" => {
+				args.mtype = match it.next().as_deref() {
+					Some("inode") => MarkType::Inode,
+					Some("mount") => MarkType::Mount,
+					Some("fs" | "filesystem") => MarkType::Fs,
+					Some("mntns") => MarkType::MntNs,
+					Some(t) => return Err(format!("Unknown mark type '{t}'")),
+					None => return Err(format!("Missing value for {arg}")),
+				}
+			},
+			"-e" | "--events" => {
+				let list = it.next().ok_or(format!("Missing value for {arg}"))?;
+				args.mask = Some(parse_events(&list)?);
+			},
+			"-c" | "--count" => {
+				let n = it.next().ok_or(format!("Missing value for {arg}"))?;
+				args.count = Some(n.parse().map_err(|_| format!("Invalid count '{n}'"))?);
+			},
+			"-w" | "--window" => {
+				let ms = it.next().ok_or(format!("Missing value for {arg}"))?;
+				args.window = Duration::from_millis(ms.parse().map_err(|_| format!("Invalid window '{ms}'"))?);
+			},
+			"-f" | "--fid" => args.fid = true,
+			"-n" | "--name" => args.name = true,
+			"-j" | "--json" => args.json = true,
+			"-h" | "--help" => return Err(String::new()),
+			a if a.starts_with('-') => return Err(format!("Unknown option '{a}'")),
+			_ => args.paths.push(PathBuf::from(arg)),
+		}
+	}
+
+	if args.paths.is_empty() {
+		return Err("No paths given".to_string());
+	}
+
+	Ok(args)
+}
+
+fn parse_events(list: &str) -> Result<EventFlags, String> {
+	list.split(',')
+		.filter(|n| !n.is_empty())
+		.try_fold(EventFlags::empty(), |mask, n| match EventFlags::from_name(&n.to_ascii_uppercase()) {
+			Some(f) => Ok(mask | f),
+			None => Err(format!("Unknown event '{n}'")),
+		})
+}
+
+/// Best-effort path of the event object, resolving file handles if possible.
+fn event_path(fan: &Fanotify, evt: &Event) -> Option<String> {
+	if let Some(r) = evt.rename() {
+		let old = fan.resolve(r.old_dir, sys::O_PATH).ok()?.1.join(r.old_name);
+		let new = fan.resolve(r.new_dir, sys::O_PATH).ok()?.1.join(r.new_name);
+		return Some(format!("{} -> {}", old.display(), new.display()));
+	}
+
+	match &evt.file {
+		EventFile::Fd(f) => handle::fd_path(f).ok().map(|p| p.display().to_string()),
+		EventFile::Fh(fh) => {
+			// Prefer the directory and name, since the object itself may be gone.
+			let dir_name = evt.info.iter().find_map(|i| match i {
+				Info::DfidName(dir, name) => Some((dir, name)),
+				_ => None,
+			});
+			let path = match dir_name {
+				Some((dir, name)) => fan.resolve(dir, sys::O_PATH).ok()?.1.join(name),
+				None => fan.resolve(fh, sys::O_PATH).ok()?.1,
+			};
+
+			Some(path.display().to_string())
+		},
+		EventFile::Empty => evt.mnt_id().map(|id| format!("mnt:{id}")),
+	}
+}
+
+/// Quote and escape a string for JSON output.
+fn json_str(s: &str) -> String {
+	let mut out = String::with_capacity(s.len() + 2);
+	out.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\t' => out.push_str("\\t"),
+			c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+
+	out
+}
+
+fn print_event(fan: &Fanotify, procs: &mut ProcCache, evt: &Event, json: bool) {
+	let names: Vec<&str> = evt.mask.iter_names().map(|(n, _)| n).collect();
+	let path = event_path(fan, evt);
+	// Process may have already exited.
+	let proc = procs.lookup(evt).ok();
+	let comm = proc.as_ref().map_or("?", |p| p.comm.as_str());
+
+	if json {
+		let events: Vec<String> = names.iter().map(|n| json_str(n)).collect();
+		let path = path.as_deref().map_or("null".to_string(), json_str);
+		let exe = proc.as_ref()
+			.and_then(|p| p.exe.as_ref())
+			.map_or("null".to_string(), |e| json_str(&e.to_string_lossy()));
+		let uid = proc.as_ref().map_or("null".to_string(), |p| p.uid.to_string());
+		println!(
+			"{{\"pid\":{},\"comm\":{},\"exe\":{},\"uid\":{},\"events\":[{}],\"path\":{}}}",
+			evt.pid, json_str(comm), exe, uid, events.join(","), path
+		);
+	} else {
+		println!("{} {} {} {}", evt.pid, comm, names.join(","), path.as_deref().unwrap_or("-"));
+	}
+}
+
+fn main() -> ExitCode {
+	let args = match parse_args() {
+		Ok(a) => a,
+		Err(e) if e.is_empty() => {
+			println!("{USAGE}");
+			return ExitCode::SUCCESS;
+		},
+		Err(e) => {
+			eprintln!("{e}\n");
+			eprintln!("{USAGE}");
+			return ExitCode::FAILURE;
+		},
 	};
-	let evt_flags = EventFdFlags {
-		rdonly: true,
-		..EventFdFlags::default()
+
+	let mask = match args.mask {
+		Some(m) => m,
+		None if args.mtype == MarkType::MntNs => EventFlags::MNT,
+		None if args.mtype == MarkType::Mount => parse_events(DEFAULT_MOUNT_EVENTS).expect("Default events are valid"),
+		None => parse_events(DEFAULT_EVENTS).expect("Default events are valid"),
 	};
-	let fan = Fanotify::init(&flags, &evt_flags);
-
-	if let Err(ref e) = fan {
-		eprintln!("Fanotify init failed: {e}");
-		return;
-	}
-	let mut fan = fan.unwrap();
-
-	// Mark test file
-	//let path = Path::new("/tmp/test.txt");
-	let path = Path::new("/tmp/");
-	let mark_flags = MarkFlags::default();
-	let evt_flags = EventFlags {
-		open: true,
-		access: true,
-		modify: true,
-		create: true,
-		ondir: true,
-		..EventFlags::default()
+
+	// Other events (e.g. directory entry events) are only reported with file handles.
+	let needs_fid = !(EventFlags::FD | EventFlags::ONDIR | EventFlags::EVENT_ON_CHILD).contains(mask);
+	if args.mtype == MarkType::Mount && needs_fid {
+		eprintln!("Mount marks only allow access, modify, open, open_exec, close, and permission events.");
+		return ExitCode::FAILURE;
+	}
+
+	// Initialize the Fanotify instance
+	let mut flags = InitFlags::CLOEXEC;
+	if args.mtype == MarkType::MntNs {
+		flags |= InitFlags::REPORT_MNT;
+	} else if args.name || (needs_fid && !args.fid) {
+		// Names make directory entry events useful, so are given unless asked for handles only.
+		flags |= InitFlags::REPORT_DFID_NAME | InitFlags::REPORT_FID;
+	} else if args.fid {
+		flags |= InitFlags::REPORT_FID;
+	}
+	if mask.intersects(EventFlags::PERM) {
+		flags |= InitFlags::CLASS_CONTENT;
+	}
+	let fan = Fanotify::builder()
+		.flags(flags)
+		.event_fd_flags(EventFdFlags::RDONLY | EventFdFlags::CLOEXEC | EventFdFlags::LARGEFILE)
+		.build();
+	let mut fan = match fan {
+		Ok(f) => f,
+		Err(e) => {
+			eprintln!("Fanotify init failed: {e}");
+			return ExitCode::FAILURE;
+		},
 	};
-	if let Err(e) = fan.add_mark(path, &MarkType::Inode, &mark_flags, &evt_flags) {
-		eprintln!("Marking '{}' failed: {e}", path.display());
-		return;
+
+	for path in &args.paths {
+		// As with inotify, the default events on a directory include those on its files.
+		let mut mask = mask;
+		if args.mask.is_none() && args.mtype == MarkType::Inode && path.is_dir() {
+			mask |= EventFlags::EVENT_ON_CHILD;
+		}
+		if let Err(e) = fan.add_mark(path, &args.mtype, &MarkFlags::empty(), &mask) {
+			eprintln!("Marking '{}' failed: {e}", path.display());
+			return ExitCode::FAILURE;
+		}
 	}
 
-	// Loop until desired event(s) occurs
+	let mut fan = Coalescer::new(fan, args.window);
+	let mut procs = ProcCache::default();
 	let mut cnt = 0;
-	while cnt < 4 {
-		while let Some(e) = fan.events() {
-			println!("Encountered event from PID {}: {:?}", e.pid, e.mask);
-			cnt += 1;
+	while args.count.is_none_or(|n| cnt < n) {
+		match fan.next_event() {
+			Ok(mut e) => {
+				// On failure the access stays blocked until exit, so keep going.
+				if e.needs_response() {
+					if let Err(err) = fan.get_ref().allow(&mut e) {
+						eprintln!("Responding to event failed: {err}");
+					}
+				}
+
+				print_event(fan.get_ref(), &mut procs, &e, args.json);
+				cnt += 1;
+			},
+			Err(e) if EventError::from_io(&e) == Some(EventError::Overflow) => {
+				eprintln!("Event queue overflowed, events were lost.");
+			},
+			Err(e) => {
+				eprintln!("Reading events failed: {e}");
+				return ExitCode::FAILURE;
+			},
 		}
 	}
+
+	ExitCode::SUCCESS
 }
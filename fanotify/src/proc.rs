@@ -0,0 +1,245 @@
+//! Enrichment of events with details of the causing process, from `/proc`.
+//!
+//! An event only carries the PID of the process responsible, which may have
+//!  exited (and the PID been reused) by the time the event is read. If the
+//!  group reports pidfds (`REPORT_PIDFD`), the descriptor is used to verify
+//!  that `/proc/<pid>` still belongs to the same process. Without one, lookups
+//!  are best-effort.
+//!
+//! Lookups are cached in a small LRU [ProcCache], keyed by PID and validated
+//!  against the process start time.
+
+use crate::{Event, Result};
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::os::fd::{AsRawFd, BorrowedFd};
+use std::os::unix::ffi::OsStringExt;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::{fs, io};
+
+/// Details of a process, as read from `/proc/<pid>`.
+#[derive(Clone, Debug)]
+pub struct Process {
+	pub pid: u32,
+	/// Command name (`comm`), possibly truncated by the kernel.
+	pub comm: String,
+	/// Path of the executable, if readable (not for kernel threads).
+	pub exe: Option<PathBuf>,
+	/// Command line arguments. Empty for kernel threads and zombies.
+	pub cmdline: Vec<OsString>,
+	/// Real user ID.
+	pub uid: u32,
+	/// Effective user ID.
+	pub euid: u32,
+	/// Real group ID.
+	pub gid: u32,
+	/// Effective group ID.
+	pub egid: u32,
+	/// Cgroup path, from the unified (v2) hierarchy if mounted.
+	pub cgroup: Option<String>,
+	/// Start time in clock ticks since boot, identifying the process along
+	///  with its PID.
+	pub start_time: u64,
+}
+
+impl Process {
+	/// Read the details of process `pid`.
+	///
+	/// If `pidfd` is given, it must refer to the same process, and is used to
+	///  ensure the details were not read from a process which reused the PID.
+	///  Fails with [io::ErrorKind::NotFound] if the process has exited.
+	pub fn read(pid: u32, pidfd: Option<BorrowedFd<'_>>) -> Result<Self> {
+		let dir = ProcDir::open(pid, pidfd)?;
+		dir.process()
+	}
+}
+
+/// Handle on a `/proc/<pid>` directory, bound to one process.
+///
+/// Once opened, the directory keeps referring to the same process, even if the
+///  PID is reused. Files are read relative to the handle.
+struct ProcDir {
+	pid: u32,
+	dir: fs::File,
+}
+
+impl ProcDir {
+	fn open(pid: u32, pidfd: Option<BorrowedFd<'_>>) -> Result<Self> {
+		let dir = fs::File::open(format!("/proc/{pid}"))?;
+
+		// If the process behind the pidfd is still alive after the directory
+		//  was opened, the PID cannot have been reused in between.
+		if let Some(pidfd) = pidfd {
+			if pidfd_pid(pidfd)? != Some(pid) {
+				return Err(io::Error::new(io::ErrorKind::NotFound, "Process exited."));
+			}
+		}
+
+		Ok(Self { pid, dir })
+	}
+
+	fn path(&self, name: &str) -> PathBuf {
+		PathBuf::from(format!("/proc/self/fd/{}/{name}", self.dir.as_raw_fd()))
+	}
+
+	fn read(&self, name: &str) -> Result<Vec<u8>> {
+		fs::read(self.path(name))
+	}
+
+	fn start_time(&self) -> Result<u64> {
+		let stat = self.read("stat")?;
+		parse_start_time(&stat).ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Malformed stat file."))
+	}
+
+	fn process(&self) -> Result<Process> {
+		let start_time = self.start_time()?;
+
+		let status = String::from_utf8_lossy(&self.read("status")?).into_owned();
+		let field = |key: &str| status.lines()
+			.find_map(|l| l.strip_prefix(key))
+			.map(str::trim);
+		let ids = |key: &str| -> Result<(u32, u32)> {
+			let mut it = field(key).unwrap_or_default().split_whitespace().map(str::parse::<u32>);
+			match (it.next(), it.next()) {
+				(Some(Ok(real)), Some(Ok(effective))) => Ok((real, effective)),
+				_ => Err(io::Error::new(io::ErrorKind::InvalidData, "Malformed status file.")),
+			}
+		};
+		let (uid, euid) = ids("Uid:")?;
+		let (gid, egid) = ids("Gid:")?;
+
+		let cmdline = self.read("cmdline")?
+			.split(|&b| b == 0)
+			.filter(|a| !a.is_empty())
+			.map(|a| OsString::from_vec(a.to_vec()))
+			.collect();
+
+		Ok(Process {
+			pid: self.pid,
+			comm: field("Name:").unwrap_or_default().to_string(),
+			exe: fs::read_link(self.path("exe")).ok(),
+			cmdline,
+			uid,
+			euid,
+			gid,
+			egid,
+			cgroup: self.read("cgroup").ok().and_then(|c| parse_cgroup(&String::from_utf8_lossy(&c))),
+			start_time,
+		})
+	}
+}
+
+/// Get the PID of the process referred to by `pidfd`, or `None` if it exited.
+fn pidfd_pid(pidfd: BorrowedFd<'_>) -> Result<Option<u32>> {
+	let info = fs::read_to_string(format!("/proc/self/fdinfo/{}", pidfd.as_raw_fd()))?;
+	let pid = info.lines()
+		.find_map(|l| l.strip_prefix("Pid:"))
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Descriptor is not a pidfd."))?;
+
+	// Exited processes show as -1, and 0 if not in our PID namespace.
+	Ok(pid.trim().parse().ok().filter(|&p| p != 0))
+}
+
+/// Extract the start time (field 22) from the contents of `/proc/<pid>/stat`.
+fn parse_start_time(stat: &[u8]) -> Option<u64> {
+	// Command name may contain spaces and parentheses, so skip past the last ')'.
+	let end = stat.iter().rposition(|&b| b == b')')?;
+	let rest = std::str::from_utf8(&stat[end + 1..]).ok()?;
+
+	// Fields after the name start at 3 (state).
+	rest.split_whitespace().nth(22 - 3)?.parse().ok()
+}
+
+/// Pick the cgroup path from the contents of `/proc/<pid>/cgroup`.
+///
+/// Prefers the unified hierarchy (`0::<path>`), falling back to the first.
+fn parse_cgroup(cgroup: &str) -> Option<String> {
+	let path = |l: &str| l.splitn(3, ':').nth(2).map(str::to_string);
+	cgroup.lines()
+		.find(|l| l.starts_with("0::"))
+		.or_else(|| cgroup.lines().next())
+		.and_then(path)
+}
+
+/// Least-recently-used cache of [Process] lookups.
+#[derive(Debug)]
+pub struct ProcCache {
+	capacity: usize,
+	entries: HashMap<u32, CacheEntry>,
+	/// Incremented on every use, to order entries by recency.
+	clock: u64,
+}
+
+#[derive(Debug)]
+struct CacheEntry {
+	proc: Arc<Process>,
+	last_used: u64,
+}
+
+impl ProcCache {
+	/// Create a cache holding up to `capacity` processes.
+	pub fn new(capacity: usize) -> Self {
+		Self {
+			capacity: capacity.max(1),
+			entries: HashMap::with_capacity(capacity),
+			clock: 0,
+		}
+	}
+
+	/// Look up the process which caused `evt`, using its pidfd if present.
+	pub fn lookup(&mut self, evt: &Event) -> Result<Arc<Process>> {
+		self.get(evt.pid, evt.pidfd())
+	}
+
+	/// Look up process `pid`. See [Process::read].
+	///
+	/// A cached entry is only used if the process start time still matches,
+	///  so a reused PID is re-read.
+	pub fn get(&mut self, pid: u32, pidfd: Option<BorrowedFd<'_>>) -> Result<Arc<Process>> {
+		self.clock += 1;
+
+		let dir = ProcDir::open(pid, pidfd)?;
+		let start_time = dir.start_time()?;
+		if let Some(entry) = self.entries.get_mut(&pid) {
+			if entry.proc.start_time == start_time {
+				entry.last_used = self.clock;
+				return Ok(entry.proc.clone());
+			}
+		}
+
+		let proc = Arc::new(dir.process()?);
+		if self.entries.len() >= self.capacity && !self.entries.contains_key(&pid) {
+			let oldest = self.entries.iter()
+				.min_by_key(|(_, e)| e.last_used)
+				.map(|(&pid, _)| pid);
+			if let Some(oldest) = oldest {
+				self.entries.remove(&oldest);
+			}
+		}
+		self.entries.insert(pid, CacheEntry { proc: proc.clone(), last_used: self.clock });
+
+		Ok(proc)
+	}
+
+	/// Number of cached processes.
+	pub fn len(&self) -> usize {
+		self.entries.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.entries.is_empty()
+	}
+
+	/// Remove all cached processes.
+	pub fn clear(&mut self) {
+		self.entries.clear();
+	}
+}
+
+impl Default for ProcCache {
+	fn default() -> Self {
+		Self::new(64)
+	}
+}
@@ -0,0 +1,86 @@
+//! Asynchronous event stream, for use with the tokio runtime.
+//!
+//! Enabled with the `tokio` feature.
+
+use crate::{Event, Fanotify, Result};
+use crate::flags::InitFlags;
+
+use futures_core::Stream;
+use std::io;
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+use tokio::io::unix::AsyncFd;
+
+/// Stream of [Event]s from a [Fanotify] group registered with the runtime.
+///
+/// The group must have been initialized with `NONBLOCK`, otherwise reads
+///  would block the runtime.
+#[derive(Debug)]
+pub struct EventStream {
+	inner: AsyncFd<Fanotify>,
+}
+impl EventStream {
+	/// Register the group with the current tokio runtime.
+	///
+	/// Fails with [io::ErrorKind::InvalidInput] if the group is not
+	///  non-blocking. Must be called within a runtime context.
+	pub fn new(fan: Fanotify) -> Result<Self> {
+		if !fan.flags.contains(InitFlags::NONBLOCK) {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "Fanotify group must be initialized with NONBLOCK."));
+		}
+
+		Ok(Self {
+			inner: AsyncFd::new(fan)?,
+		})
+	}
+
+	pub fn get_ref(&self) -> &Fanotify {
+		self.inner.get_ref()
+	}
+
+	/// Mutable access to the group (e.g. for marking, or responding to events).
+	pub fn get_mut(&mut self) -> &mut Fanotify {
+		self.inner.get_mut()
+	}
+
+	/// Deregister from the runtime, returning the group.
+	pub fn into_inner(self) -> Fanotify {
+		self.inner.into_inner()
+	}
+}
+impl Stream for EventStream {
+	type Item = Result<Event>;
+
+	fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+		let this = self.get_mut();
+		if let Some(e) = this.inner.get_mut().deferred_err.take() {
+			return Poll::Ready(Some(Err(e)));
+		}
+
+		loop {
+			// Drain events from the previous read first.
+			match this.inner.get_mut().next_buffered() {
+				Ok(Some(evt)) => return Poll::Ready(Some(Ok(evt))),
+				Ok(None) => {},
+				Err(e) => return Poll::Ready(Some(Err(e))),
+			}
+
+			let mut guard = ready!(this.inner.poll_read_ready_mut(cx))?;
+			match guard.try_io(|inner| inner.get_mut().fill_buffer()) {
+				// Readiness was stale, so wait again.
+				Err(_would_block) => continue,
+				// Reads never return zero bytes while the group is open.
+				Ok(Ok(0)) => return Poll::Ready(None),
+				Ok(Ok(_)) => continue,
+				Ok(Err(e)) => return Poll::Ready(Some(Err(e))),
+			}
+		}
+	}
+}
+
+impl Fanotify {
+	/// Convert into an [EventStream]. See [EventStream::new].
+	pub fn into_stream(self) -> Result<EventStream> {
+		EventStream::new(self)
+	}
+}
@@ -13,6 +13,8 @@ pub const O_DSYNC: i32 = 0o00010000;
 pub const O_NOATIME: i32 = 0o01000000;
 pub const O_NONBLOCK: i32 = 0o00004000;
 pub const O_SYNC: i32 = 0o04010000; // (__O_SYNC=04000000 | O_DSYNC)
+/// Used when opening handles/mount descriptors only needed for lookups.
+pub const O_PATH: i32 = 0o010000000;
 
 /* Events that user-space can register for */
 /// File was accessed
@@ -60,6 +62,11 @@ pub const FAN_EVENT_ON_CHILD: i32 = 0x08000000;
 /// File was renamed
 pub const FAN_RENAME: i32 = 0x10000000;
 
+/// Mount was attached to the namespace
+pub const FAN_MNT_ATTACH: i32 = 0x01000000;
+/// Mount was detached from the namespace
+pub const FAN_MNT_DETACH: i32 = 0x02000000;
+
 /// Event occurred against dir
 pub const FAN_ONDIR: i32 = 0x40000000;
 
@@ -94,6 +101,8 @@ pub const FAN_REPORT_DIR_FID: i32 = 0x00000400;
 pub const FAN_REPORT_NAME: i32 = 0x00000800;
 /// Report dirent target id
 pub const FAN_REPORT_TARGET_FID: i32 = 0x00001000;
+/// Report mount events
+pub const FAN_REPORT_MNT: i32 = 0x00004000;
 
 /* Convenience macro - FAN_REPORT_NAME requires FAN_REPORT_DIR_FID */
 pub const FAN_REPORT_DFID_NAME: i32 = FAN_REPORT_DIR_FID | FAN_REPORT_NAME;
@@ -122,9 +131,13 @@ pub const FAN_MARK_IGNORE: i32 = 0x00000400;
 pub const FAN_MARK_INODE: i32 = 0x00000000;
 pub const FAN_MARK_MOUNT: i32 = 0x00000010;
 pub const FAN_MARK_FILESYSTEM: i32 = 0x00000100;
+pub const FAN_MARK_MNTNS: i32 = 0x00000110;
 
 pub const FAN_MARK_IGNORE_SURV: i32 = FAN_MARK_IGNORE | FAN_MARK_IGNORED_SURV_MODIFY;
 
+/// Special `dirfd` value to resolve relative paths from the working directory.
+pub const AT_FDCWD: i32 = -100;
+
 pub const FANOTIFY_METADATA_VERSION: i32 = 3;
 
 pub const FAN_EVENT_INFO_TYPE_FID: i32 = 1;
@@ -132,6 +145,8 @@ pub const FAN_EVENT_INFO_TYPE_DFID_NAME: i32 = 2;
 pub const FAN_EVENT_INFO_TYPE_DFID: i32 = 3;
 pub const FAN_EVENT_INFO_TYPE_PIDFD: i32 = 4;
 pub const FAN_EVENT_INFO_TYPE_ERROR: i32 = 5;
+/* FAN_EVENT_INFO_TYPE_RANGE is             6 */
+pub const FAN_EVENT_INFO_TYPE_MNT: i32 = 7;
 
 /* Special info types for FAN_RENAME */
 pub const FAN_EVENT_INFO_TYPE_OLD_DFID_NAME: i32 = 10;
@@ -165,6 +180,41 @@ extern "C" {
 		dirfd: ffi::c_int,
 		pathname: *const ffi::c_char
 	) -> ffi::c_int;
+
+	/// `handle` is a [file_handle] followed by `handle_bytes` bytes of handle.
+	pub fn open_by_handle_at(mount_fd: ffi::c_int, handle: *const file_handle, flags: ffi::c_int) -> ffi::c_int;
+	pub fn fstatfs(fd: ffi::c_int, buf: *mut statfs) -> ffi::c_int;
+	pub fn poll(fds: *mut pollfd, nfds: ffi::c_ulong, timeout: ffi::c_int) -> ffi::c_int;
+}
+
+/* poll() event bits */
+pub const POLLIN: i16 = 0x001;
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct pollfd {
+	pub fd: ffi::c_int,
+	pub events: ffi::c_short,
+	pub revents: ffi::c_short
+}
+
+/// Layout for 64-bit Linux targets (`__fsword_t` is `long`).
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct statfs {
+	pub f_type: ffi::c_long,
+	pub f_bsize: ffi::c_long,
+	pub f_blocks: u64,
+	pub f_bfree: u64,
+	pub f_bavail: u64,
+	pub f_files: u64,
+	pub f_ffree: u64,
+	/// Same value fanotify reports in `event_info_fid.fsid`.
+	pub f_fsid: fsid_t,
+	pub f_namelen: ffi::c_long,
+	pub f_frsize: ffi::c_long,
+	pub f_flags: ffi::c_long,
+	pub f_spare: [ffi::c_long; 4]
 }
 
 #[repr(C)]
@@ -203,15 +253,15 @@ pub struct event_info_fid {
 	pub file_handle: file_handle,
 }
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct fsid_t {
-	val: [u32; 2] // May need something to convert to 64 bit
+	pub val: [u32; 2] // May need something to convert to 64 bit
 }
 #[repr(C)]
-#[derive(Clone, Copy, Debug)]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct file_handle {
 	pub handle_bytes: u32,
-	_handle_type: i32,
+	pub handle_type: i32,
 	//f_handle: [u8; file_handle.handle_bytes] // Kernel fanotify inline file handle bytes
 	// If filename present, filename is null-terminated bytes following
 	pub handle: [u8; 0]
@@ -224,7 +274,7 @@ pub struct event_info_pidfd {
 	// No different from value as obtained by pidfd_open on event_metadata.pid
 	// May be FAN_NOPIDFD
 	// MUST be closed once event is dealth with
-	pub pidfd: u32
+	pub pidfd: i32
 }
 
 #[repr(C)]
@@ -238,6 +288,15 @@ pub struct event_info_error {
 	pub error_count: u32
 }
 
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct event_info_mnt {
+	/// `info_type` set to `FAN_EVENT_INFO_TYPE_MNT`
+	pub hdr: event_info_header,
+	/// Unique mount ID, as from `statx()` with `STATX_MNT_ID_UNIQUE`
+	pub mnt_id: u64
+}
+
 #[repr(C)]
 #[derive(Clone, Copy)]
 pub struct response {
@@ -0,0 +1,55 @@
+#![cfg(feature = "fake")]
+
+use fanotify::fake::{self, FakeEvent};
+use fanotify::flags::*;
+use fanotify::EventError;
+use std::time::{Duration, Instant};
+
+#[test]
+fn wait_timeout() {
+	let (fan, source) = fake::pair(&InitFlags::NONBLOCK).unwrap();
+	assert!(!fan.has_pending_events().unwrap());
+
+	let start = Instant::now();
+	assert!(!fan.wait_for_events(Some(Duration::from_millis(20))).unwrap());
+	assert!(start.elapsed() >= Duration::from_millis(20));
+
+	source.send([FakeEvent::new(EventFlags::MODIFY, 1)]).unwrap();
+	assert!(fan.has_pending_events().unwrap());
+	assert!(fan.wait_for_events(None).unwrap());
+}
+
+#[test]
+fn deferred_batch_error() {
+	let (mut fan, source) = fake::pair(&InitFlags::NONBLOCK).unwrap();
+	let mut buf = [
+		FakeEvent::new(EventFlags::MODIFY, 1).into_bytes(),
+		FakeEvent::new(EventFlags::MODIFY, 2).into_bytes(),
+	].concat();
+	buf.extend_from_slice(&[0; 8]);
+	source.send_raw(&buf).unwrap();
+
+	// Events before the error are returned, and the error with the next call.
+	let batch = fan.read_batch().unwrap();
+	assert_eq!(batch.iter().map(|e| e.pid).collect::<Vec<_>>(), [1, 2]);
+	assert!(fan.has_pending_events().unwrap());
+	let err = fan.read_batch().unwrap_err();
+	assert_eq!(EventError::from_io(&err), Some(EventError::Truncated));
+
+	// Nothing remains once reported.
+	assert!(!fan.has_pending_events().unwrap());
+	assert!(fan.read_batch().unwrap().is_empty());
+}
+
+#[test]
+fn batch_keeps_buffered_events() {
+	let (mut fan, source) = fake::pair(&InitFlags::NONBLOCK).unwrap();
+	source.send([FakeEvent::new(EventFlags::MODIFY, 1), FakeEvent::new(EventFlags::MODIFY, 2)]).unwrap();
+	source.send([FakeEvent::new(EventFlags::MODIFY, 3)]).unwrap();
+
+	// Events left by a previous read are returned without reading again.
+	assert_eq!(fan.events().unwrap().unwrap().pid, 1);
+	assert!(fan.has_pending_events().unwrap());
+	assert_eq!(fan.read_batch().unwrap().iter().map(|e| e.pid).collect::<Vec<_>>(), [2]);
+	assert_eq!(fan.read_batch().unwrap().iter().map(|e| e.pid).collect::<Vec<_>>(), [3]);
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "fake")]
+
+mod common;
+
+use common::handle;
+use fanotify::coalesce::Coalescer;
+use fanotify::fake::{self, FakeEvent};
+use fanotify::flags::*;
+use fanotify::EventError;
+use std::time::Duration;
+
+#[test]
+fn coalesce_events() {
+	let (fan, source) = fake::pair(&(InitFlags::NONBLOCK | InitFlags::REPORT_FID)).unwrap();
+	source.send([
+		FakeEvent::new(EventFlags::MODIFY, 1).fid(&handle(1)),
+		FakeEvent::new(EventFlags::MODIFY, 2).fid(&handle(1)),
+		FakeEvent::new(EventFlags::CLOSE_WRITE, 1).fid(&handle(1)),
+		FakeEvent::new(EventFlags::MODIFY, 1).fid(&handle(2)),
+	]).unwrap();
+
+	let mut co = Coalescer::new(fan, Duration::from_millis(10)).max_pending(2);
+	let evt = co.next_event().unwrap();
+	assert_eq!((evt.pid, evt.mask), (1, EventFlags::MODIFY | EventFlags::CLOSE_WRITE));
+	let evt = co.next_event().unwrap();
+	assert_eq!((evt.pid, evt.mask), (2, EventFlags::MODIFY));
+
+	let stats = co.stats();
+	assert_eq!((stats.received, stats.merged, stats.dropped, stats.emitted), (4, 1, 1, 2));
+	assert!(co.flush().is_empty());
+}
+
+#[test]
+fn uncoalesced_events() {
+	let (fan, source) = fake::pair(&(InitFlags::NONBLOCK | InitFlags::REPORT_FID)).unwrap();
+	source.send([
+		FakeEvent::new(EventFlags::MODIFY, 1).fid(&handle(1)),
+		FakeEvent::new(EventFlags::FS_ERROR, 0).error(5, 1).fid(&handle(1)),
+		FakeEvent::new(EventFlags::MODIFY, 1).fid(&handle(1)),
+	]).unwrap();
+
+	// Errors are passed on immediately, ahead of held events.
+	let mut co = Coalescer::new(fan, Duration::from_secs(60));
+	assert_eq!(co.next_event().unwrap().mask, EventFlags::FS_ERROR);
+	assert_eq!(co.pending(), 1);
+	assert_eq!(co.flush().len(), 1);
+
+	// Nothing is held without a window.
+	source.send([
+		FakeEvent::new(EventFlags::MODIFY, 1).fid(&handle(1)),
+		FakeEvent::new(EventFlags::MODIFY, 1).fid(&handle(1)),
+	]).unwrap();
+	let mut co = Coalescer::new(co.into_inner(), Duration::ZERO);
+	co.next_event().unwrap();
+	co.next_event().unwrap();
+	assert_eq!(co.stats().merged, 0);
+
+	source.send([FakeEvent::new(EventFlags::Q_OVERFLOW, 0)]).unwrap();
+	let err = co.next_event().unwrap_err();
+	assert_eq!(EventError::from_io(&err), Some(EventError::Overflow));
+	assert_eq!(co.stats().overflows, 1);
+}
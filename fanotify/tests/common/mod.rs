@@ -0,0 +1,18 @@
+//! Fixtures shared by the tests.
+
+// Each test file uses only some of these.
+#![allow(dead_code)]
+
+use fanotify::{sys, FileHandle};
+use std::fs;
+use std::os::fd::OwnedFd;
+
+/// A distinct file handle for each `n`, on the same filesystem.
+pub fn handle(n: u8) -> FileHandle {
+	FileHandle::new(sys::fsid_t { val: [1, 2] }, 1, &[n; 8])
+}
+
+/// A descriptor to give with events.
+pub fn dev_null() -> OwnedFd {
+	fs::File::open("/dev/null").unwrap().into()
+}
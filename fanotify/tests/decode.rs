@@ -0,0 +1,97 @@
+#![cfg(feature = "fake")]
+
+mod common;
+
+use common::{dev_null, handle};
+use fanotify::fake::{self, FakeEvent};
+use fanotify::flags::*;
+use fanotify::{sys, EventDecoder, EventError, EventFile, Info};
+use std::io;
+use std::os::fd::AsRawFd;
+
+#[test]
+fn fid_records() {
+	let flags = InitFlags::REPORT_FID | InitFlags::REPORT_DFID_NAME;
+	let buf = [
+		FakeEvent::new(EventFlags::CREATE, 10).dfid_name(&handle(1), "name").fid(&handle(2)).into_bytes(),
+		FakeEvent::new(EventFlags::DELETE_SELF, 11).dfid(&handle(3)).into_bytes(),
+	].concat();
+
+	// SAFETY: Events contain no descriptors.
+	let mut events = unsafe { EventDecoder::new(&buf, flags) };
+	let evt = events.next().unwrap().unwrap();
+	assert_eq!(evt.mask, EventFlags::CREATE);
+	assert_eq!(evt.pid, 10);
+	assert!(matches!(&evt.file, EventFile::Fh(fh) if *fh == handle(2)));
+	assert!(matches!(&evt.info[0], Info::DfidName(fh, name) if *fh == handle(1) && name == "name"));
+
+	// Only a directory handle, so it identifies the event object.
+	let evt = events.next().unwrap().unwrap();
+	assert!(matches!(&evt.file, EventFile::Fh(fh) if *fh == handle(3)));
+	assert!(events.next().is_none());
+}
+
+#[test]
+fn malformed_events() {
+	let decode = |buf: &[u8]| -> Vec<Option<EventError>> {
+		// SAFETY: Events contain no descriptors.
+		unsafe { EventDecoder::new(buf, InitFlags::REPORT_FID) }
+			.map(|r| r.err().and_then(|e| EventError::from_io(&e)))
+			.collect()
+	};
+
+	// Overflow is reported, then decoding continues.
+	let buf = [
+		FakeEvent::new(EventFlags::Q_OVERFLOW, 0).into_bytes(),
+		FakeEvent::new(EventFlags::CREATE, 1).fid(&handle(1)).into_bytes(),
+	].concat();
+	assert_eq!(decode(&buf), [Some(EventError::Overflow), None]);
+
+	// Nothing after a bad length or version can be trusted.
+	let mut buf = FakeEvent::new(EventFlags::CREATE, 1).into_bytes();
+	buf.extend_from_slice(&buf.clone()[..8]);
+	assert_eq!(decode(&buf), [None, Some(EventError::Truncated)]);
+	buf[4] = 2;
+	assert_eq!(decode(&buf), [Some(EventError::Version(2))]);
+
+	// Malformed info records are skipped, keeping the event.
+	let buf = FakeEvent::new(EventFlags::CREATE, 1).fid(&handle(1)).info_raw(&[1, 0, 0xFF, 0]).into_bytes();
+	// SAFETY: Event contains no descriptors.
+	let evt = unsafe { EventDecoder::new(&buf, InitFlags::REPORT_FID) }.next().unwrap().unwrap();
+	assert_eq!(evt.info.len(), 1);
+}
+
+#[test]
+fn malformed_permission_event() {
+	let (mut fan, source) = fake::pair(&(InitFlags::NONBLOCK | InitFlags::CLASS_CONTENT)).unwrap();
+	let fd = dev_null();
+	let raw = fd.as_raw_fd();
+	let mut buf = FakeEvent::new(EventFlags::OPEN_PERM, 1).fd(fd).into_bytes();
+	// Event length extends past the data read.
+	let len = buf.len() as u32 + 8;
+	buf[..4].copy_from_slice(&len.to_ne_bytes());
+	source.send_raw(&buf).unwrap();
+
+	// Accessing process is released, rather than blocked until the group closes.
+	let err = fan.events().unwrap_err();
+	assert_eq!(EventError::from_io(&err), Some(EventError::Truncated));
+	let resp = source.response().unwrap();
+	assert_eq!((resp.fd, resp.response), (raw, sys::FAN_ALLOW));
+	assert!(fan.events().unwrap().is_none());
+}
+
+#[test]
+fn partial_decode() {
+	let first = FakeEvent::new(EventFlags::OPEN, 1).into_bytes();
+	let buf = [first.clone(), FakeEvent::new(EventFlags::CLOSE_WRITE, 2).into_bytes()].concat();
+
+	// SAFETY: Events contain no descriptors.
+	let mut events = unsafe { EventDecoder::new(&buf, InitFlags::empty()) };
+	assert_eq!(events.remaining().len(), buf.len());
+	assert_eq!(events.next().unwrap().unwrap().mask, EventFlags::OPEN);
+	assert_eq!(events.remaining(), &buf[first.len()..]);
+
+	// Reads are a single datagram, so cannot exceed the read buffer.
+	let (_fan, source) = fake::pair(&InitFlags::NONBLOCK).unwrap();
+	assert_eq!(source.send_raw(&[0; 4097]).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+}
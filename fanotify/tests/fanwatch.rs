@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
+use std::{env, fs, process, thread};
+
+fn fanwatch(args: &[&str]) -> Command {
+	let mut cmd = Command::new(env!("CARGO_BIN_EXE_fanwatch"));
+	cmd.args(args);
+	cmd
+}
+
+fn stderr(out: &Output) -> String {
+	String::from_utf8_lossy(&out.stderr).into_owned()
+}
+
+/// Watch a new directory, repeating `act` on it until the watch exits.
+fn watch(args: &[&str], dir: &str, act: impl Fn(&Path)) -> String {
+	let dir = env::temp_dir().join(format!("fanwatch-{dir}-{}", process::id()));
+	fs::create_dir_all(&dir).unwrap();
+
+	let mut child = fanwatch(args).arg(&dir).stdout(Stdio::piped()).spawn().unwrap();
+	// Marks are only added some time after starting, so retry until seen.
+	while child.try_wait().unwrap().is_none() {
+		act(&dir);
+		thread::sleep(Duration::from_millis(10));
+	}
+	let out = child.wait_with_output().unwrap();
+	assert!(out.status.success(), "{}", stderr(&out));
+	fs::remove_dir_all(&dir).unwrap();
+
+	String::from_utf8(out.stdout).unwrap()
+}
+
+/// Watch a new directory, creating `name` in it until the watch exits.
+fn watch_create(args: &[&str], dir: &str, name: &str) -> String {
+	watch(args, dir, |dir| {
+		fs::write(dir.join(name), "").unwrap();
+		fs::remove_file(dir.join(name)).unwrap();
+	})
+}
+
+fn path_of(dir: &str, name: &str) -> PathBuf {
+	Path::new(&env::temp_dir()).join(format!("fanwatch-{dir}-{}", process::id())).join(name)
+}
+
+#[test]
+fn invalid_args() {
+	let cases: [(&[&str], &str); 6] = [
+		(&[], "No paths given"),
+		(&["-x", "/"], "Unknown option '-x'"),
+		(&["-e", "create,bogus", "/"], "Unknown event 'bogus'"),
+		(&["-c", "many", "/"], "Invalid count 'many'"),
+		(&["-t", "tree", "/"], "Unknown mark type 'tree'"),
+		(&["-w"], "Missing value for -w"),
+	];
+	for (args, msg) in cases {
+		let out = fanwatch(args).output().unwrap();
+		assert!(!out.status.success());
+		assert!(stderr(&out).contains(msg), "{args:?}: {}", stderr(&out));
+		assert!(stderr(&out).contains("Usage: fanwatch"));
+	}
+
+	let out = fanwatch(&["--help"]).output().unwrap();
+	assert!(out.status.success());
+	assert!(String::from_utf8_lossy(&out.stdout).starts_with("Usage: fanwatch"));
+
+	// Checked before anything is marked, since the kernel only gives EINVAL.
+	let out = fanwatch(&["-t", "mount", "-e", "create", "/"]).output().unwrap();
+	assert!(!out.status.success());
+	assert!(stderr(&out).starts_with("Mount marks only allow"), "{}", stderr(&out));
+}
+
+#[test]
+fn default_events() {
+	// Directory entry events are watched, so names are reported. The kernel
+	//  may merge the creation and removal into one event.
+	let out = watch_create(&["-c", "1"], "default", "new");
+	let fields: Vec<&str> = out.lines().next().unwrap().split(' ').collect();
+	assert_eq!(fields.len(), 4, "{out}");
+	assert_eq!(fields[0], process::id().to_string());
+	assert!(fields[2].split(',').any(|e| e == "CREATE"), "{out}");
+	assert_eq!(fields[3], path_of("default", "new").to_str().unwrap());
+
+	// Files already in the directory are watched too.
+	let file = path_of("child", "old");
+	fs::create_dir_all(file.parent().unwrap()).unwrap();
+	fs::write(&file, "").unwrap();
+	let out = watch(&["-c", "1"], "child", |_| fs::write(&file, "x").unwrap());
+	let fields: Vec<&str> = out.lines().next().unwrap().split(' ').collect();
+	assert!(fields[2].split(',').any(|e| e == "MODIFY"), "{out}");
+	assert_eq!(fields[3], file.to_str().unwrap());
+}
+
+#[test]
+fn json_output() {
+	let out = watch_create(&["-c", "1", "-j", "-e", "create"], "json", "a\"b");
+	let line = out.lines().next().unwrap();
+	let path = path_of("json", "a\\\"b");
+	assert!(line.starts_with(&format!("{{\"pid\":{},\"comm\":\"", process::id())), "{line}");
+	assert!(line.ends_with(&format!("\"events\":[\"CREATE\"],\"path\":\"{}\"}}", path.display())), "{line}");
+}
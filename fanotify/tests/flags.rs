@@ -0,0 +1,68 @@
+use fanotify::flags::*;
+use fanotify::{sys, Fanotify};
+use std::io;
+
+#[test]
+fn bits_round_trip() {
+	for bits in [InitFlags::all().bits(), sys::FAN_REPORT_DFID_NAME | sys::FAN_NONBLOCK] {
+		assert_eq!(InitFlags::from_bits(bits).map(|f| f.bits()), Some(bits));
+	}
+	for bits in [EventFlags::all().bits(), sys::FAN_FS_ERROR, sys::FAN_RENAME | sys::FAN_ONDIR] {
+		assert_eq!(EventFlags::from_bits(bits).map(|f| f.bits()), Some(bits));
+	}
+	let bits = MarkFlags::all().bits();
+	assert_eq!(MarkFlags::from_bits(bits).map(|f| f.bits()), Some(bits));
+	let bits = EventFdFlags::all().bits();
+	assert_eq!(EventFdFlags::from_bits(bits).map(|f| f.bits()), Some(bits));
+
+	// Mark action and type bits are not flags.
+	assert_eq!(MarkFlags::from_bits(sys::FAN_MARK_ADD), None);
+	assert_eq!(MarkFlags::from_bits_truncate(sys::FAN_MARK_ADD | sys::FAN_MARK_ONLYDIR), MarkFlags::ONLYDIR);
+}
+
+#[test]
+fn composite_flags() {
+	let mask = EventFlags::CLOSE_WRITE | EventFlags::CLOSE_NOWRITE;
+	assert_eq!(mask, EventFlags::CLOSE);
+	assert!(mask.contains(EventFlags::CLOSE_WRITE));
+	assert!(!EventFlags::CLOSE_WRITE.contains(EventFlags::CLOSE));
+	assert_eq!(format!("{:?}", EventFlags::CLOSE | EventFlags::ONDIR), "EventFlags(CLOSE | ONDIR)");
+}
+
+#[test]
+fn invalid_init_flags() {
+	let cases = [
+		(InitFlags::CLASS_CONTENT | InitFlags::CLASS_PRE_CONTENT, FlagError::MultipleClasses),
+		(InitFlags::REPORT_TID | InitFlags::REPORT_PIDFD, FlagError::TidWithPidfd),
+		(InitFlags::CLASS_CONTENT | InitFlags::REPORT_FID, FlagError::FidWithContentClass),
+		(InitFlags::REPORT_NAME, FlagError::NameWithoutDirFid),
+		(InitFlags::REPORT_DIR_FID | InitFlags::REPORT_TARGET_FID, FlagError::TargetFidIncomplete),
+		(InitFlags::REPORT_MNT | InitFlags::REPORT_FID, FlagError::MntWithFileReporting),
+		(InitFlags::REPORT_MNT | InitFlags::CLASS_CONTENT, FlagError::MntWithFileReporting),
+	];
+	for (flags, err) in cases {
+		assert_eq!(flags.validate(), Err(err), "{flags:?}");
+	}
+
+	assert!(InitFlags::REPORT_DFID_NAME_TARGET.validate().is_ok());
+	assert!((InitFlags::CLASS_CONTENT | InitFlags::REPORT_TID).validate().is_ok());
+}
+
+#[test]
+fn builder_rejects_before_init() {
+	let err = Fanotify::builder()
+		.flags(InitFlags::REPORT_TID | InitFlags::REPORT_PIDFD)
+		.build()
+		.unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+
+	let builder = Fanotify::builder().event_fd_flags(EventFdFlags::WRONLY | EventFdFlags::RDWR);
+	assert_eq!(builder.validate(), Err(FlagError::AccessMode));
+}
+
+#[test]
+fn invalid_mark_flags() {
+	assert_eq!((MarkFlags::IGNORE | MarkFlags::IGNORED_MASK).validate(), Err(FlagError::IgnoreConflict));
+	assert_eq!(MarkFlags::IGNORED_SURV_MODIFY.validate(), Err(FlagError::SurvModifyWithoutIgnore));
+	assert!(MarkFlags::IGNORE_SURV.validate().is_ok());
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "fake")]
+
+mod common;
+
+use common::handle;
+use fanotify::fake::{self, FakeEvent};
+use fanotify::flags::*;
+use fanotify::{sys, EventDecoder, FileHandle};
+use std::io;
+
+#[test]
+fn error_records() {
+	let empty = FileHandle::new(sys::fsid_t { val: [1, 2] }, 0, &[]);
+	let buf = [
+		FakeEvent::new(EventFlags::FS_ERROR, 0).error(5, 3).fid(&empty).into_bytes(),
+		FakeEvent::new(EventFlags::FS_ERROR, 0).error(117, 1).fid(&handle(1)).into_bytes(),
+	].concat();
+
+	// SAFETY: Events contain no descriptors.
+	let mut events = unsafe { EventDecoder::new(&buf, InitFlags::REPORT_FID) };
+	let err = events.next().unwrap().unwrap().fs_error().unwrap();
+	assert_eq!((err.errno, err.count, err.fsid.val), (5, 3, [1, 2]));
+	assert!(err.handle.is_none());
+
+	let err = events.next().unwrap().unwrap().fs_error().unwrap();
+	assert_eq!(err.io_error().raw_os_error(), Some(117));
+	assert_eq!(err.handle, Some(handle(1)));
+}
+
+#[test]
+fn error_mark_requires_fid() {
+	let (mut fan, _source) = fake::pair(&InitFlags::empty()).unwrap();
+	assert_eq!(fan.add_fs_error_mark("/").unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+	// Only FS_ERROR events are errors.
+	let buf = FakeEvent::new(EventFlags::MODIFY, 0).error(5, 1).fid(&handle(1)).into_bytes();
+	// SAFETY: Event contains no descriptors.
+	let evt = unsafe { EventDecoder::new(&buf, InitFlags::REPORT_FID) }.next().unwrap().unwrap();
+	assert!(evt.fs_error().is_none());
+}
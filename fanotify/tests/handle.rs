@@ -0,0 +1,37 @@
+#![cfg(feature = "fake")]
+
+use fanotify::fake;
+use fanotify::flags::InitFlags;
+use fanotify::handle::{fsid_of, MountRegistry};
+use fanotify::{sys, FileHandle};
+use std::{env, fs, io};
+use std::os::fd::AsRawFd;
+
+#[test]
+fn registry_lookup() {
+	let mut mounts = MountRegistry::new();
+	let dir = env::temp_dir();
+	let fsid = mounts.insert(&dir).unwrap();
+	assert_eq!(fsid, fsid_of(fs::File::open(&dir).unwrap()).unwrap());
+	assert!(mounts.get(&fsid).is_some());
+
+	// Registering the same filesystem again keeps the first descriptor.
+	let fd = mounts.get(&fsid).unwrap().as_raw_fd();
+	assert_eq!(mounts.insert(dir.join(".")).unwrap(), fsid);
+	assert_eq!(mounts.get(&fsid).unwrap().as_raw_fd(), fd);
+
+	let other = sys::fsid_t { val: [fsid.val[0] ^ 1, fsid.val[1]] };
+	assert!(mounts.get(&other).is_none());
+}
+
+#[test]
+fn unregistered_mount() {
+	let fh = FileHandle::new(sys::fsid_t { val: [1, 2] }, 1, &[0; 8]);
+
+	let mounts = MountRegistry::new();
+	assert_eq!(mounts.open(&fh, sys::O_RDONLY).unwrap_err().kind(), io::ErrorKind::NotFound);
+
+	// Nothing is marked on a fake group, so nothing resolves.
+	let (fan, _source) = fake::pair(&InitFlags::REPORT_FID).unwrap();
+	assert_eq!(fan.resolve(&fh, sys::O_RDONLY).unwrap_err().kind(), io::ErrorKind::NotFound);
+}
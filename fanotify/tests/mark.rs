@@ -0,0 +1,72 @@
+use fanotify::flags::*;
+use fanotify::Fanotify;
+use std::os::fd::AsFd;
+use std::path::PathBuf;
+use std::{env, fs, io, process};
+
+/// Group watching modification of a new file, named `name` in its own directory.
+fn watch(name: &str) -> (Fanotify, PathBuf) {
+	let dir = env::temp_dir().join(format!("fanotify-{name}-{}", process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	fs::write(dir.join(name), "").unwrap();
+
+	// FID reporting allows unprivileged groups.
+	let mut fan = Fanotify::builder().flags(InitFlags::NONBLOCK | InitFlags::REPORT_FID).build().unwrap();
+	fan.add_mark(dir.join(name), &MarkType::Inode, &MarkFlags::empty(), &EventFlags::MODIFY).unwrap();
+
+	(fan, dir)
+}
+
+fn flag_error(err: io::Error) -> FlagError {
+	*err.into_inner().unwrap().downcast::<FlagError>().unwrap()
+}
+
+#[test]
+fn remove_mark() {
+	let (mut fan, dir) = watch("remove");
+	let file = dir.join("remove");
+	fs::write(&file, "a").unwrap();
+	assert!(fan.events().unwrap().is_some());
+
+	fan.remove_mark(&file, &MarkType::Inode, &MarkFlags::empty(), &EventFlags::MODIFY).unwrap();
+	fs::write(&file, "b").unwrap();
+	assert!(fan.events().unwrap().is_none());
+
+	// Mark was removed once empty.
+	let err = fan.remove_mark(&file, &MarkType::Inode, &MarkFlags::empty(), &EventFlags::MODIFY).unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::NotFound);
+	fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn ignore_mark_at() {
+	let (mut fan, dir) = watch("ignore");
+	let dirfd = fs::File::open(&dir).unwrap();
+
+	fan.add_ignore_mark_at(Some(dirfd.as_fd()), "ignore", &MarkType::Inode, &EventFlags::MODIFY, true).unwrap();
+	fs::write(dir.join("ignore"), "a").unwrap();
+	fs::write(dir.join("ignore"), "b").unwrap();
+	assert!(fan.events().unwrap().is_none());
+
+	fan.remove_mark_at(Some(dirfd.as_fd()), "ignore", &MarkType::Inode, &MarkFlags::IGNORE, &EventFlags::MODIFY).unwrap();
+	fs::write(dir.join("ignore"), "c").unwrap();
+	assert!(fan.events().unwrap().is_some());
+	fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn ignore_requires_surv_modify() {
+	let (mut fan, dir) = watch("surv");
+
+	// Directories are rejected by the kernel, mounts and filesystems beforehand.
+	let err = fan.add_ignore_mark(&dir, &MarkType::Inode, &EventFlags::OPEN, false).unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::IsADirectory);
+	let err = fan.add_ignore_mark(&dir, &MarkType::Mount, &EventFlags::OPEN, false).unwrap_err();
+	assert_eq!(flag_error(err), FlagError::IgnoreWithoutSurvModify);
+	let err = fan.add_mark(&dir, &MarkType::Fs, &MarkFlags::IGNORE, &EventFlags::OPEN).unwrap_err();
+	assert_eq!(flag_error(err), FlagError::IgnoreWithoutSurvModify);
+
+	let err = fan.remove_mark(&dir, &MarkType::Inode, &(MarkFlags::IGNORE | MarkFlags::IGNORED_MASK), &EventFlags::OPEN).unwrap_err();
+	assert_eq!(flag_error(err), FlagError::IgnoreConflict);
+	fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,25 @@
+#![cfg(feature = "fake")]
+
+use fanotify::fake::{self, FakeEvent};
+use fanotify::flags::*;
+use fanotify::EventFile;
+
+#[test]
+fn mnt_records() {
+	let (mut fan, source) = fake::pair(&(InitFlags::NONBLOCK | InitFlags::REPORT_MNT)).unwrap();
+	source.send([FakeEvent::new(EventFlags::MNT_ATTACH, 1).mnt(0x8000_0001)]).unwrap();
+
+	let evt = fan.events().unwrap().unwrap();
+	assert!(matches!(evt.file, EventFile::Empty));
+	assert_eq!(evt.mnt_id(), Some(0x8000_0001));
+
+	// Other mark types and events are rejected before reaching the kernel.
+	let mark = |fan: &mut fanotify::Fanotify, mtype, mask| {
+		fan.add_mark("/proc/self/ns/mnt", &mtype, &MarkFlags::empty(), &mask)
+			.map_err(|e| *e.into_inner().unwrap().downcast::<FlagError>().unwrap())
+	};
+	assert_eq!(mark(&mut fan, MarkType::Inode, EventFlags::MNT), Err(FlagError::MntNsMark));
+	assert_eq!(mark(&mut fan, MarkType::MntNs, EventFlags::MNT | EventFlags::OPEN), Err(FlagError::MntNsMark));
+	let (mut fan, _source) = fake::pair(&InitFlags::empty()).unwrap();
+	assert_eq!(mark(&mut fan, MarkType::MntNs, EventFlags::MNT), Err(FlagError::MntNsMark));
+}
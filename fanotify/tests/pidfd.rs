@@ -0,0 +1,38 @@
+#![cfg(feature = "fake")]
+
+mod common;
+
+use common::dev_null;
+use fanotify::fake::{self, FakeEvent};
+use fanotify::flags::*;
+use fanotify::{Info, PidFd};
+use std::os::fd::AsRawFd;
+
+#[test]
+fn pidfd_records() {
+	let (mut fan, source) = fake::pair(&(InitFlags::NONBLOCK | InitFlags::REPORT_PIDFD)).unwrap();
+	source.send([
+		FakeEvent::new(EventFlags::OPEN, 1).fd(dev_null()).pidfd(Some(dev_null())),
+		FakeEvent::new(EventFlags::OPEN, 2).fd(dev_null()).pidfd(None),
+	]).unwrap();
+
+	let mut batch = fan.read_batch().unwrap();
+	assert_eq!(batch.len(), 2);
+	assert!(batch[0].pidfd().is_some());
+	let pidfd = batch[0].take_pidfd().unwrap();
+	assert!(pidfd.as_raw_fd() >= 0);
+	assert!(matches!(batch[0].info[..], [Info::PidFd(PidFd::Taken)]));
+	assert!(matches!(batch[1].info[..], [Info::PidFd(PidFd::Exited)]));
+
+	assert!(fan.events().unwrap().is_none());
+}
+
+#[test]
+fn thread_ids() {
+	let (mut fan, source) = fake::pair(&(InitFlags::NONBLOCK | InitFlags::REPORT_TID)).unwrap();
+	source.send([FakeEvent::new(EventFlags::OPEN, 5).fd(dev_null())]).unwrap();
+
+	let evt = fan.events().unwrap().unwrap();
+	assert!(evt.pid_is_tid());
+	assert!(evt.pidfd().is_none());
+}
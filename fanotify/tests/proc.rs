@@ -0,0 +1,25 @@
+use fanotify::proc::{ProcCache, Process};
+use std::sync::Arc;
+
+#[test]
+fn read_self() {
+	let proc = Process::read(std::process::id(), None).unwrap();
+	assert_eq!(proc.pid, std::process::id());
+	assert_eq!(proc.exe, std::env::current_exe().ok());
+	assert_eq!(proc.cmdline.first().cloned(), std::env::args_os().next());
+	assert!(proc.start_time > 0);
+}
+
+#[test]
+fn cache_eviction() {
+	let mut cache = ProcCache::new(1);
+	let me = cache.get(std::process::id(), None).unwrap();
+	assert!(Arc::ptr_eq(&me, &cache.get(std::process::id(), None).unwrap()));
+
+	// Init always exists, and evicts the least recently used entry.
+	cache.get(1, None).unwrap();
+	assert_eq!(cache.len(), 1);
+	assert!(!Arc::ptr_eq(&me, &cache.get(std::process::id(), None).unwrap()));
+
+	assert!(cache.get(u32::MAX, None).is_err());
+}
@@ -0,0 +1,36 @@
+#![cfg(feature = "fake")]
+
+mod common;
+
+use common::handle;
+use fanotify::fake::FakeEvent;
+use fanotify::flags::*;
+use fanotify::EventDecoder;
+
+#[test]
+fn rename_records() {
+	let buf = FakeEvent::new(EventFlags::RENAME, 1)
+		.old_dfid_name(&handle(1), "old")
+		.new_dfid_name(&handle(2), "new")
+		.into_bytes();
+
+	// SAFETY: Event contains no descriptors.
+	let evt = unsafe { EventDecoder::new(&buf, InitFlags::REPORT_DFID_NAME) }.next().unwrap().unwrap();
+	let rename = evt.rename().unwrap();
+	assert_eq!((rename.old_dir, rename.old_name.to_str()), (&handle(1), Some("old")));
+	assert_eq!((rename.new_dir, rename.new_name.to_str()), (&handle(2), Some("new")));
+}
+
+#[test]
+fn rename_requires_both_records() {
+	// Other events, or renames missing a side, are not reported as renames.
+	let buf = [
+		FakeEvent::new(EventFlags::MOVED_TO, 1).dfid_name(&handle(2), "new").into_bytes(),
+		FakeEvent::new(EventFlags::RENAME, 1).old_dfid_name(&handle(1), "old").into_bytes(),
+	].concat();
+
+	// SAFETY: Events contain no descriptors.
+	let mut events = unsafe { EventDecoder::new(&buf, InitFlags::REPORT_DFID_NAME) };
+	assert!(events.next().unwrap().unwrap().rename().is_none());
+	assert!(events.next().unwrap().unwrap().rename().is_none());
+}
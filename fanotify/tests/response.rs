@@ -0,0 +1,80 @@
+#![cfg(feature = "fake")]
+
+mod common;
+
+use common::dev_null;
+use fanotify::fake::{self, FakeEvent};
+use fanotify::flags::*;
+use fanotify::{sys, AuditRule, Event, EventFile, Response};
+use std::io;
+use std::os::fd::AsRawFd;
+
+#[test]
+fn permission_response() {
+	let (mut fan, source) = fake::pair(&(InitFlags::CLASS_CONTENT | InitFlags::ENABLE_AUDIT)).unwrap();
+	source.send([FakeEvent::new(EventFlags::OPEN_PERM, 1).fd(dev_null())]).unwrap();
+
+	let mut evt = fan.events().unwrap().unwrap();
+	assert!(evt.needs_response());
+	let EventFile::Fd(ref file) = evt.file else { panic!("Expected descriptor") };
+	let fd = file.as_raw_fd();
+	fan.respond(&mut evt, Response::DenyAudit).unwrap();
+	assert!(!evt.needs_response());
+
+	let resp = source.response().unwrap();
+	assert_eq!(resp.fd, fd);
+	assert_eq!(resp.response, sys::FAN_DENY | sys::FAN_AUDIT);
+	assert!(resp.audit_rule.is_none());
+
+	// Only one response is allowed.
+	assert_eq!(fan.allow(&mut evt).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn audit_rule_response() {
+	let (mut fan, source) = fake::pair(&(InitFlags::CLASS_CONTENT | InitFlags::ENABLE_AUDIT)).unwrap();
+	source.send([FakeEvent::new(EventFlags::ACCESS_PERM, 1).fd(dev_null())]).unwrap();
+
+	let mut evt = fan.events().unwrap().unwrap();
+	let rule = AuditRule { rule_number: 7, subj_trust: 1, obj_trust: 2 };
+	fan.respond(&mut evt, Response::DenyAuditRule(rule)).unwrap();
+
+	let resp = source.response().unwrap();
+	assert_eq!(resp.response, sys::FAN_DENY | sys::FAN_AUDIT | sys::FAN_INFO);
+	let sent = resp.audit_rule.unwrap();
+	assert_eq!((sent.rule_number, sent.subj_trust, sent.obj_trust), (7, 1, 2));
+}
+
+#[test]
+fn unanswered_events() {
+	let (mut fan, source) = fake::pair(&(InitFlags::NONBLOCK | InitFlags::CLASS_CONTENT)).unwrap();
+	source.send([
+		FakeEvent::new(EventFlags::OPEN, 1).fd(dev_null()),
+		FakeEvent::new(EventFlags::OPEN_PERM, 1).fd(dev_null()),
+		FakeEvent::new(EventFlags::OPEN_PERM, 1).fd(dev_null()),
+	]).unwrap();
+
+	// Notification events cannot be responded to.
+	let mut evt = fan.events().unwrap().unwrap();
+	assert!(!evt.needs_response());
+	assert_eq!(fan.allow(&mut evt).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+
+	// Dropping a permission event without a response allows it, and only then.
+	let fd = |evt: &Event| match evt.file {
+		EventFile::Fd(ref f) => f.as_raw_fd(),
+		_ => panic!("Expected descriptor"),
+	};
+	let mut answered = fan.events().unwrap().unwrap();
+	let answered_fd = fd(&answered);
+	fan.deny(&mut answered).unwrap();
+	drop(answered);
+	let dropped = fan.events().unwrap().unwrap();
+	let dropped_fd = fd(&dropped);
+	assert!(dropped.needs_response());
+	drop(dropped);
+
+	let resp = source.response().unwrap();
+	assert_eq!((resp.fd, resp.response), (answered_fd, sys::FAN_DENY));
+	let resp = source.response().unwrap();
+	assert_eq!((resp.fd, resp.response), (dropped_fd, sys::FAN_ALLOW));
+}
@@ -0,0 +1,38 @@
+#![cfg(all(feature = "fake", feature = "tokio"))]
+
+use fanotify::fake::{self, FakeEvent};
+use fanotify::flags::*;
+use fanotify::stream::EventStream;
+use fanotify::{sys, Event, FileHandle};
+use futures_core::Stream;
+use std::future;
+use std::io;
+use std::pin::Pin;
+
+async fn next(stream: &mut EventStream) -> Option<io::Result<Event>> {
+	future::poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+#[tokio::test]
+async fn requires_nonblock() {
+	let (fan, _source) = fake::pair(&InitFlags::empty()).unwrap();
+	assert_eq!(EventStream::new(fan).unwrap_err().kind(), io::ErrorKind::InvalidInput);
+}
+
+#[tokio::test]
+async fn stream_events() {
+	let (fan, source) = fake::pair(&(InitFlags::NONBLOCK | InitFlags::REPORT_FID)).unwrap();
+	let mut stream = fan.into_stream().unwrap();
+
+	// Sent once the stream is waiting on the group.
+	let fh = FileHandle::new(sys::fsid_t { val: [1, 2] }, 1, &[1; 8]);
+	let send = async {
+		tokio::task::yield_now().await;
+		source.send([FakeEvent::new(EventFlags::CREATE, 1).fid(&fh)]).unwrap();
+	};
+	let (evt, _) = tokio::join!(next(&mut stream), send);
+
+	let evt = evt.unwrap().unwrap();
+	assert_eq!((evt.pid, evt.mask), (1, EventFlags::CREATE));
+	assert!(stream.get_mut().events().unwrap().is_none());
+}
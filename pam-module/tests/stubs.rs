@@ -0,0 +1,20 @@
+use pam::mock::{MockPam, SmFn};
+use pam::{PamFlags, PamResult};
+
+#[test]
+fn stubs_ignore() {
+	let stubs: [SmFn; 6] = [
+		test_pam::pam_sm_authenticate,
+		test_pam::pam_sm_setcred,
+		test_pam::pam_sm_open_session,
+		test_pam::pam_sm_close_session,
+		test_pam::pam_sm_acct_mgmt,
+		test_pam::pam_sm_chauthtok,
+	];
+
+	let mut pam = MockPam::new("test").user("alice");
+	for f in stubs {
+		assert_eq!(pam.call(f, PamFlags::empty(), &["debug"]), PamResult::Ignore);
+	}
+	assert!(pam.messages().is_empty());
+}
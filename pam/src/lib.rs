@@ -1,16 +1,22 @@
 pub use pam_sm_macro::*;
 
-use types::*;
+pub use options::*;
+pub use types::*;
 
 use std::{
 	borrow::Cow,
 	ffi::{self, CString, CStr},
 	marker,
-	mem::MaybeUninit,
+	mem::{self, MaybeUninit},
+	panic,
+	ptr,
+	sync::atomic,
+	time::Duration,
 };
-use libc;
-
 mod macros;
+#[cfg(feature = "mock")]
+pub mod mock;
+pub mod options;
 mod sys;
 pub mod types;
 
@@ -23,152 +29,290 @@ pub struct PamMessage<'s> {
 	pub msg: Cow<'s, str>,
 }
 
+/// Rust fail delay callback. See [PamConv::fail_delay].
+type DelayFn = Box<dyn FnMut(PamResult, Duration)>;
+
 // TODO: Change string slice to some custom type that tries to allocate as
 //        responses are added, allowing user to handle allocation errors.
-struct ConvData<'s, T: Sized> {
-	fun: fn(&[PamMessage<'s>], &mut [String], &mut T) -> PamResult,
+/// User conversation function and data, boxed as the `appdata_ptr` of a [PamConv].
+#[repr(C)]
+struct ConvData<F, T> {
+	/// First, so [fail_delay_wrapper] can find it without knowing `F` and `T`.
+	delay: Option<DelayFn>,
+	fun: F,
 	data: T,
 }
 
 /// Wrapper for user conv function, used in [PamConv] as the `pam_conv.conv`
 ///  function.
-fn conv_wrapper<'s, T>(
+///
+/// Responses are only given to the caller if the user function succeeds. The
+///  user's copies are zeroed either way, since they may hold passwords.
+///
+/// # Safety
+/// `data` must point to the `ConvData<F, T>` of the [PamConv] being called.
+unsafe extern "C" fn conv_wrapper<F, T>(
 	count: i32,
 	msgs: *const *const sys::pam_message,
 	responses: *mut *mut sys::pam_response,
 	data: *const ()
- ) -> i32 {
-	// Early check of response pointer.
-	if responses.is_null() {
-		return sys::PAM_BUF_ERR
-	}
-
-	// Allocate responses array early and return PAM_BUF_ERR if so. Saves
-	//  effort of doing anything if this fails.
-	// SAFETY: Responses is non-null after above conditional.
-	unsafe {
-		*responses = libc::malloc(count as usize * std::mem::size_of::<*mut sys::pam_response>()) as _;
-		if (*responses).is_null() {
-			return sys::PAM_BUF_ERR
-		}
+ ) -> i32
+where
+	F: FnMut(&[PamMessage<'_>], &mut [String], &mut T) -> PamResult,
+{
+	if responses.is_null() || msgs.is_null() || data.is_null() {
+		return sys::PAM_CONV_ERR
 	}
-
-	// TODO: Box ConvData.
-	let conv_data = unsafe { &mut (data as *mut ConvData<'s, T>).read() };
-
-	// Initialize arrays for user fn
-	let mut pam_messages = Vec::new();
-	let pam_responses = &mut Vec::with_capacity(count as usize);
-	pam_responses.resize(count as usize, String::new());
-
-	// Convert input data to rust data for user fn.
-	for i in (0..count) {
-		let msg: &sys::pam_message = unsafe {
-			&(*msgs).add(i as usize).read()
-		};
+	// SAFETY: Checked non-null above.
+	unsafe { *responses = ptr::null_mut() };
+	if count <= 0 || count > sys::PAM_MAX_NUM_MSG {
+		return sys::PAM_CONV_ERR
+	}
+	let count = count as usize;
+
+	// SAFETY: Caller guarantees `data` is the boxed ConvData, which outlives the call.
+	let conv_data = unsafe { &mut *(data as *mut ConvData<F, T>) };
+
+	// Convert input data to rust data for user fn. Linux-PAM gives an array of
+	//  pointers to messages (unlike Solaris, a pointer to an array of messages).
+	let mut pam_messages = Vec::with_capacity(count);
+	for i in 0..count {
+		// SAFETY: Caller gives `count` message pointers.
+		let msg = unsafe { *msgs.add(i) };
+		if msg.is_null() {
+			return sys::PAM_CONV_ERR
+		}
+		// SAFETY: Checked non-null above.
+		let msg = unsafe { &*msg };
 
 		// Convert the style.
-		let style = match PamConvType::try_from(msg.msg_style) {
-			Ok(v) => v,
-			Err(_) => PamConvType::TextInfo,
-		};
+		let style = PamConvType::try_from(msg.msg_style).unwrap_or(PamConvType::TextInfo);
 
 		// Get message as string slice
-		let text = unsafe {
-			String::from_utf8_lossy(CStr::from_ptr(msg.msg).to_bytes())
+		let text = match msg.msg.is_null() {
+			true => Cow::Borrowed(""),
+			false => unsafe { String::from_utf8_lossy(CStr::from_ptr(msg.msg).to_bytes()) },
 		};
 
-		// Construct message wrapper.
-		let message = PamMessage {
+		pam_messages.push(PamMessage {
 			style,
 			msg: text,
-		};
-
-		// Put wrapper into vector for user.
-		pam_messages.push(message);
+		});
 	}
 
-	// Call user fn.
-	let res = (conv_data.fun)(&pam_messages, pam_responses, &mut conv_data.data);
+	// Call user fn. Unwinding into C is undefined, so a panic fails the conversation.
+	let mut pam_responses = vec![String::new(); count];
+	let res = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+		(conv_data.fun)(&pam_messages, &mut pam_responses, &mut conv_data.data)
+	})).unwrap_or(PamResult::ConvErr);
 
 	// If successful, convert responses to libc-allocated array of strings.
-	// Else leave `*responses` untouched.
-	if res == PamResult::Success {
-		unsafe {
-			// Iterate responses, then allocate and copy each.
-			for i in (0..count) {
-				// Allocate response
-				let resp = &pam_responses[i as usize];
-				let c_resp = libc::malloc(resp.len() + 1) as *mut u8; // THIS IS BAD. See TODO on [ConvData].
-
-				// Copy response
-				std::ptr::copy_nonoverlapping(resp.as_ptr(), c_resp, resp.len());
-				c_resp.add(resp.len()).write(0); // Null-terminator.
-
-				// Add response to `*responses`
-				*(*responses).add(i as usize) = sys::pam_response {
-					resp: c_resp as *mut i8,
-					_resp_retcode: 0,
-				};
-			}
-		}
+	// Else leave `*responses` null.
+	let res = match res {
+		PamResult::Success => match unsafe { alloc_responses(&pam_responses) } {
+			Some(resp) => {
+				unsafe { *responses = resp };
+				PamResult::Success
+			},
+			None => PamResult::BufErr,
+		},
+		e => e,
+	};
+
+	for resp in &mut pam_responses {
+		// SAFETY: Zero bytes are valid UTF-8.
+		zeroize(unsafe { resp.as_bytes_mut() });
 	}
 
 	res as i32
  }
 
-/// Newtype wrapper for [pam_conv].
+/// Wrapper for the [PamConv::fail_delay] callback, used as the `PAM_FAIL_DELAY`
+///  item.
+///
+/// # Safety
+/// `data` must be the `appdata_ptr` of a [PamConv] created by [PamConv::new].
+unsafe extern "C" fn fail_delay_wrapper(status: i32, usec: u32, data: *const ()) {
+	if data.is_null() {
+		return
+	}
+
+	// SAFETY: The callback is the first field of every ConvData.
+	if let Some(delay) = unsafe { &mut *(data as *mut Option<DelayFn>) } {
+		let status = PamResult::try_from(status).unwrap_or(PamResult::SystemErr);
+		// Unwinding into C is undefined, and there is no error to report.
+		_ = panic::catch_unwind(panic::AssertUnwindSafe(|| delay(status, Duration::from_micros(usec.into()))));
+	}
+}
+
+/// Copy responses into a malloc'd array, as returned by a conversation.
+///
+/// Returns `None` if any allocation fails, freeing anything allocated.
+unsafe fn alloc_responses(src: &[String]) -> Option<*mut sys::pam_response> {
+	// Zeroed, so any response not yet allocated is null.
+	let arr = libc::calloc(src.len(), mem::size_of::<sys::pam_response>()) as *mut sys::pam_response;
+	if arr.is_null() {
+		return None
+	}
+
+	for (i, resp) in src.iter().enumerate() {
+		let c_resp = libc::malloc(resp.len() + 1) as *mut u8;
+		if c_resp.is_null() {
+			free_responses(arr, src.len());
+			return None
+		}
+
+		ptr::copy_nonoverlapping(resp.as_ptr(), c_resp, resp.len());
+		c_resp.add(resp.len()).write(0); // Null-terminator.
+		(*arr.add(i)).resp = c_resp as *mut ffi::c_char;
+	}
+
+	Some(arr)
+}
+
+/// Zero and free a response array of `count` entries, as returned by a
+///  conversation.
+unsafe fn free_responses(arr: *mut sys::pam_response, count: usize) {
+	for i in 0..count {
+		let resp = (*arr.add(i)).resp;
+		if !resp.is_null() {
+			zeroize(std::slice::from_raw_parts_mut(resp as *mut u8, libc::strlen(resp)));
+			libc::free(resp as _);
+		}
+	}
+	libc::free(arr as _);
+}
+
+/// Overwrite `buf` with zeros, without the writes being optimized away.
+fn zeroize(buf: &mut [u8]) {
+	for b in buf.iter_mut() {
+		// SAFETY: Pointer is from a valid reference.
+		unsafe { ptr::write_volatile(b, 0) };
+	}
+	atomic::compiler_fence(atomic::Ordering::SeqCst);
+}
+
+/// Free the boxed [ConvData] of a [PamConv].
 ///
-// TODO: Allow construction of PamConv from function/closure + data.
-// TODO: Implement [Drop].
-pub struct PamConv(sys::pam_conv);
+/// # Safety
+/// `ptr` must be from [Box::into_raw] of a `ConvData<F, T>`, and not used after.
+unsafe fn free_conv_data<F, T>(ptr: *const ()) {
+	drop(Box::from_raw(ptr as *mut ConvData<F, T>));
+}
+
+/// Wrapper for [sys::pam_conv].
+///
+/// Conversations created with [PamConv::new] own their function and data,
+///  which are freed on drop. Those obtained from a handle are only borrowed.
+pub struct PamConv {
+	conv: sys::pam_conv,
+	/// Frees `conv.appdata_ptr`, if owned.
+	free: Option<unsafe fn(*const ())>,
+	/// `PAM_FAIL_DELAY` item to set with this conversation.
+	delay: Option<PamDelayFn>,
+}
 impl PamConv {
-	// TODO: Complete. Change return type to [Self].
-	pub fn new<'s, T>(conv_fn: fn(&[PamMessage<'s>],&mut [String],&mut T), data: T) {
+	/// Create a conversation which calls `conv_fn` with `data`.
+	///
+	/// `conv_fn` is given every message of a conversation, and sets the
+	///  response for each (ignored for messages which are not prompts). Any
+	///  result other than [PamResult::Success] fails the conversation, and
+	///  responses are discarded.
+	pub fn new<F, T>(conv_fn: F, data: T) -> Self
+	where
+		F: FnMut(&[PamMessage<'_>], &mut [String], &mut T) -> PamResult + 'static,
+		T: 'static,
+	{
+		let conv_data = Box::new(ConvData { delay: None, fun: conv_fn, data });
+
+		Self {
+			conv: sys::pam_conv {
+				conv: conv_wrapper::<F, T>,
+				appdata_ptr: Box::into_raw(conv_data) as *const (),
+			},
+			free: Some(free_conv_data::<F, T>),
+			delay: None,
+		}
+	}
 
+	/// Call `delay` instead of sleeping after a failure, with the result and
+	///  delay requested by modules (e.g. to sleep asynchronously).
+	///
+	/// The callback is set as the `PAM_FAIL_DELAY` item along with the
+	///  conversation. Has no effect on conversations obtained from a handle.
+	pub fn fail_delay<D>(mut self, delay: D) -> Self
+	where
+		D: FnMut(PamResult, Duration) + 'static,
+	{
+		if self.free.is_some() {
+			// SAFETY: Owned, so `appdata_ptr` is a ConvData, whose first field is the callback.
+			unsafe { *(self.conv.appdata_ptr as *mut Option<DelayFn>) = Some(Box::new(delay)) };
+			self.delay = Some(fail_delay_wrapper);
+		}
+
+		self
 	}
 
 	/// Safe call method for contained PAM conversation function.
 	///
 	pub fn call(&self, conv_type: PamConvType, prompt: &str) -> Result<String> {
-		// Convert prompt to CString for function call
-		let text = CString::new(prompt).unwrap();
-		let msg = sys::pam_message {
-			msg_style: conv_type as i32,
-			msg: text.as_ptr(),
+		let msg = PamMessage {
+			style: conv_type,
+			msg: Cow::Borrowed(prompt),
 		};
-		let msg_p = &msg as *const sys::pam_message;
 
-		// Create response pointer
-		let mut resp: MaybeUninit<*mut sys::pam_response> = MaybeUninit::uninit();
-
-		// Call conv and then extract response if successful
-		let res = unsafe { (self.0.conv)(1, &msg_p, resp.as_mut_ptr(), self.0.appdata_ptr) };
-		if res == PamResult::Success as i32 {
-			// SAFETY: Valid pointer on success.
-			let resp = unsafe { resp.assume_init() };
+		self.converse(&[msg]).map(|mut resps| resps.swap_remove(0))
+	}
 
-			// Extract/copy response to owned string
-			// SAFETY: `resp` points to a valid struct because successful return code.
-			let s = unsafe {
-				if (*resp).resp.is_null() {
-					String::new()
-				} else {
-					CStr::from_ptr((*resp).resp).to_str().unwrap_or_default().to_owned()
-				}
-			};
+	/// Send all `msgs` in one conversation, returning the response to each.
+	///
+	/// Responses to messages which are not prompts are typically empty.
+	pub fn converse(&self, msgs: &[PamMessage<'_>]) -> Result<Vec<String>> {
+		// Convert messages to C strings, then the array of pointers to them.
+		let texts = msgs.iter()
+			.map(|m| CString::new(m.msg.as_bytes()).map_err(|_| PamResult::ConvErr))
+			.collect::<Result<Vec<_>>>()?;
+		let c_msgs: Vec<sys::pam_message> = msgs.iter().zip(&texts)
+			.map(|(m, text)| sys::pam_message {
+				msg_style: m.style as i32,
+				msg: text.as_ptr(),
+			})
+			.collect();
+		let c_msg_ptrs: Vec<*const sys::pam_message> = c_msgs.iter().map(|m| m as *const _).collect();
+
+		// Call conv and then extract responses if successful
+		let mut resp: *mut sys::pam_response = ptr::null_mut();
+		let res = unsafe {
+			(self.conv.conv)(c_msg_ptrs.len() as i32, c_msg_ptrs.as_ptr(), &mut resp, self.conv.appdata_ptr)
+		};
+		if res != PamResult::Success as i32 {
+			return Err(PamResult::try_from(res).unwrap_or(PamResult::ConvErr));
+		}
+		if resp.is_null() {
+			return Err(PamResult::ConvErr);
+		}
 
-			// Free the given response string.
-			// NOTE: Since resp was allocated with malloc (libc), can't just hold in Box or CString.
-			unsafe {
-				libc::free((*resp).resp as _);
-				libc::free(resp as _);
-			}
+		// Copy responses to owned strings, then free the given (malloc'd) ones.
+		// SAFETY: Successful return gives one response per message.
+		let out = unsafe {
+			let out = (0..msgs.len())
+				.map(|i| match (*resp.add(i)).resp {
+					r if r.is_null() => String::new(),
+					r => CStr::from_ptr(r).to_string_lossy().into_owned(),
+				})
+				.collect();
+			free_responses(resp, msgs.len());
+			out
+		};
 
-			// Return response.
-			Ok(s)
-		} else {
-			Err(PamResult::try_from(res).unwrap())
+		Ok(out)
+	}
+}
+impl Drop for PamConv {
+	fn drop(&mut self) {
+		if let Some(free) = self.free {
+			// SAFETY: Only set by [PamConv::new], with the matching types.
+			unsafe { free(self.conv.appdata_ptr) }
 		}
 	}
 }
@@ -177,7 +321,10 @@ impl PamConv {
 ///
 /// Because the PAM library is not thread-safe with respect to a given handle,
 ///  all functions require mutable references to ensure safe usage.
-pub struct PamHandle<'d>(*const (), PamResult, marker::PhantomData<&'d i32>);
+///
+/// Conversations set with [PamHandle::pam_set_item] are kept until the handle
+///  is dropped, since PAM only copies the `pam_conv` struct.
+pub struct PamHandle<'d>(*const (), PamResult, marker::PhantomData<&'d i32>, Vec<PamConv>);
 impl<'d> PamHandle<'d> {
 	/// Construct an instance using a raw handle as obtained through the
 	///  `pam_sm_*` functions.
@@ -187,23 +334,31 @@ impl<'d> PamHandle<'d> {
 	// TODO: Get the conv item and store for uniformity with new() and ease of
 	//        dropping.
 	pub unsafe fn from_raw(ptr: *const ()) -> Self {
-		Self(ptr, PamResult::Success, marker::PhantomData)
+		Self(ptr, PamResult::Success, marker::PhantomData, Vec::new())
 	}
 
 	/// Start a new pam session.
-	pub fn new(svc: &str, user: &str, conv: &PamConv) -> Result<Self> {
+	///
+	/// `conv` is used for the whole transaction, so must outlive the handle.
+	pub fn new(svc: &str, user: &str, conv: &'d PamConv) -> Result<Self> {
 		let svc_c = CString::new(svc).unwrap();
 		let user_c = CString::new(user).unwrap();
 		let mut handle: MaybeUninit<*const ()> = MaybeUninit::uninit();
 		let res = unsafe {
-			sys::pam_start(svc_c.as_ptr(), user_c.as_ptr(), &conv.0 as *const _, handle.as_mut_ptr())
+			sys::pam_start(svc_c.as_ptr(), user_c.as_ptr(), &conv.conv as *const _, handle.as_mut_ptr())
 		};
 
-		if res == sys::PAM_SUCCESS {
-			unsafe { Ok(Self (handle.assume_init(), PamResult::Success, marker::PhantomData)) }
-		} else {
-			Err(PamResult::try_from(res).unwrap())
+		if res != sys::PAM_SUCCESS {
+			return Err(PamResult::try_from(res).unwrap())
 		}
+
+		let mut pamh = unsafe { Self(handle.assume_init(), PamResult::Success, marker::PhantomData, Vec::new()) };
+		if conv.delay.is_some() {
+			// SAFETY: The delay function expects the data of this conversation.
+			unsafe { pamh.pam_set_fail_delay(conv.delay)? };
+		}
+
+		Ok(pamh)
 	}
 
 	/// Update last return value of self, and return converted value.
@@ -213,65 +368,193 @@ impl<'d> PamHandle<'d> {
 		r
 	}
 
+	/// Same as [PamHandle::ret], as a [Result] for calls returning nothing else.
+	fn ret_unit(&mut self, rv: i32) -> Result<()> {
+		match self.ret(rv) {
+			PamResult::Success => Ok(()),
+			e => Err(e),
+		}
+	}
+
+	/// Authenticate the user, typically by prompting for a password.
+	///
+	/// Flags may be [PamFlags::SILENT] and/or [PamFlags::DISALLOW_NULL_AUTHTOK].
+	pub fn pam_authenticate(&mut self, flags: PamFlags) -> Result<()> {
+		let rv = unsafe { sys::pam_authenticate(self.0, flags.bits()) };
+		self.ret_unit(rv)
+	}
+
+	/// Check the (authenticated) user's account is valid, e.g. not expired.
+	///
+	/// Fails with [PamResult::NewAuthTokReqd] if the user must change their
+	///  authentication token (see [PamHandle::pam_chauthtok]).
+	pub fn pam_acct_mgmt(&mut self, flags: PamFlags) -> Result<()> {
+		let rv = unsafe { sys::pam_acct_mgmt(self.0, flags.bits()) };
+		self.ret_unit(rv)
+	}
+
+	/// Establish, delete, or refresh the user's credentials (e.g. group
+	///  memberships or tickets), as given by `cred`.
+	///
+	/// Credentials should be established after [PamHandle::pam_acct_mgmt],
+	///  and before [PamHandle::pam_open_session].
+	pub fn pam_setcred(&mut self, cred: PamCred, flags: PamFlags) -> Result<()> {
+		let rv = unsafe { sys::pam_setcred(self.0, cred as i32 | flags.bits()) };
+		self.ret_unit(rv)
+	}
+
+	/// Open a session for the (authenticated) user.
+	pub fn pam_open_session(&mut self, flags: PamFlags) -> Result<()> {
+		let rv = unsafe { sys::pam_open_session(self.0, flags.bits()) };
+		self.ret_unit(rv)
+	}
+
+	/// Close a session opened with [PamHandle::pam_open_session].
+	pub fn pam_close_session(&mut self, flags: PamFlags) -> Result<()> {
+		let rv = unsafe { sys::pam_close_session(self.0, flags.bits()) };
+		self.ret_unit(rv)
+	}
+
+	/// Change the user's authentication token.
+	///
+	/// Flags may be [PamFlags::SILENT] and/or [PamFlags::CHANGE_EXPIRED_AUTHTOK].
+	pub fn pam_chauthtok(&mut self, flags: PamFlags) -> Result<()> {
+		let rv = unsafe { sys::pam_chauthtok(self.0, flags.bits()) };
+		self.ret_unit(rv)
+	}
+
+	/// Get the value of a PAM environment variable.
+	pub fn pam_getenv<S: AsRef<str>>(&mut self, name: S) -> Option<String> {
+		let c_name = CString::new(name.as_ref()).ok()?;
+		let val = unsafe { sys::pam_getenv(self.0, c_name.as_ptr()) };
+
+		if val.is_null() {
+			return None;
+		}
+
+		// SAFETY: Non-null return is a valid C-string, owned by the handle.
+		let val = unsafe { CStr::from_ptr(val) };
+		Some(val.to_string_lossy().into_owned())
+	}
+
+	/// Set (`NAME=value`) or delete (`NAME`) a PAM environment variable.
+	///
+	/// Fails with [PamResult::BadItem] if `name_value` contains a null byte.
+	pub fn pam_putenv<S: AsRef<str>>(&mut self, name_value: S) -> Result<()> {
+		let c_name_value = CString::new(name_value.as_ref()).map_err(|_| PamResult::BadItem)?;
+		let rv = unsafe { sys::pam_putenv(self.0, c_name_value.as_ptr()) };
+		self.ret_unit(rv)
+	}
+
+	/// Get all PAM environment variables, as name and value pairs.
+	///
+	/// Typically used to set up the environment of the user's session.
+	pub fn pam_getenvlist(&mut self) -> Result<Vec<(String, String)>> {
+		let list = unsafe { sys::pam_getenvlist(self.0) };
+		if list.is_null() {
+			return Err(PamResult::BufErr);
+		}
+
+		let mut vars = Vec::new();
+		// SAFETY: List is a null-terminated array of C-strings, which must all be freed.
+		unsafe {
+			let mut entry = list;
+			while !(*entry).is_null() {
+				let var = CStr::from_ptr(*entry).to_string_lossy();
+				let (name, value) = var.split_once('=').unwrap_or((&var, ""));
+				vars.push((name.to_string(), value.to_string()));
+
+				libc::free(*entry as _);
+				entry = entry.add(1);
+			}
+			libc::free(list as _);
+		}
+
+		Ok(vars)
+	}
+
+	/// Get a description of a PAM error.
+	pub fn pam_strerror(&self, err: PamResult) -> String {
+		let s = unsafe { sys::pam_strerror(self.0, err as i32) };
+		if s.is_null() {
+			return String::new();
+		}
+
+		// SAFETY: Non-null return is a valid C-string.
+		unsafe { CStr::from_ptr(s) }.to_string_lossy().into_owned()
+	}
+
 	/// Gets the item [PamItemType] associated with this handle.
 	///
+	/// Unset string items are empty.
 	pub fn pam_get_item(&mut self, item_type: PamItemType) -> Result<PamItem> {
-		match item_type {
+		let mut item: *const () = ptr::null();
+		let res = unsafe { self.ret(
+			sys::pam_get_item(self.0, item_type as i32, &mut item)
+		)};
+		if res != PamResult::Success {
+			return Err(res)
+		}
+
+		// SAFETY: Successful return code implies data is null or the item's type.
+		let string = || match item.is_null() {
+			true => String::new(),
+			false => unsafe { CStr::from_ptr(item as *const ffi::c_char) }.to_string_lossy().into_owned(),
+		};
+
+		Ok(match item_type {
+			PamItemType::Service => PamItem::Service(string()),
+			PamItemType::User => PamItem::User(string()),
+			PamItemType::UserPrompt => PamItem::UserPrompt(string()),
+			PamItemType::Tty => PamItem::Tty(string()),
+			PamItemType::Ruser => PamItem::Ruser(string()),
+			PamItemType::Rhost => PamItem::Rhost(string()),
+			PamItemType::AuthTok => PamItem::AuthTok(string()),
+			PamItemType::OldAuthTok => PamItem::OldAuthTok(string()),
+			PamItemType::XDisplay => PamItem::XDisplay(string()),
+			PamItemType::AuthTokType => PamItem::AuthTokType(string()),
 			PamItemType::Conv => {
-				let mut item: MaybeUninit<*const sys::pam_conv> = MaybeUninit::uninit();
-				let res = unsafe { self.ret(
-					sys::pam_get_item(self.0, item_type as i32, item.as_mut_ptr() as _)
-				)};
-
-				if res == PamResult::Success {
-					// SAFETY: Successful return implies struct pointer and struct are valid.
-					let conv_struct = unsafe { item.assume_init() };
-
-					unsafe { Ok(PamItem::Conv(PamConv(*conv_struct))) }
-				} else {
-					Err(res)
+				if item.is_null() {
+					return Err(PamResult::BadItem)
 				}
+
+				// SAFETY: Checked non-null above.
+				let conv = unsafe { *(item as *const sys::pam_conv) };
+				PamItem::Conv(PamConv { conv, free: None, delay: None })
 			},
-			// For everything else (all c-string values).
-			_ => {
-				let mut item: MaybeUninit<*const ffi::c_char> = MaybeUninit::uninit();
-				let res = unsafe { self.ret(
-					sys::pam_get_item(self.0, item_type as i32, item.as_mut_ptr() as _)
-				)};
-
-				if res == PamResult::Success {
-					// SAFETY: Successful return code implies data is valid C-string.
-					let item = unsafe { item.assume_init() };
-					let s = match item.is_null() {
-						true => String::new(),
-						false => {
-							let item_cstr = unsafe { CStr::from_ptr(item) };
-							item_cstr.to_str().unwrap_or_default().to_string()
-						},
-					};
-
-					match item_type {
-						PamItemType::Service => Ok(PamItem::Service(s)),
-						PamItemType::User => Ok(PamItem::User(s)),
-						PamItemType::UserPrompt => Ok(PamItem::UserPrompt(s)),
-						PamItemType::Tty => Ok(PamItem::Tty(s)),
-						PamItemType::Ruser => Ok(PamItem::Ruser(s)),
-						PamItemType::Rhost => Ok(PamItem::Rhost(s)),
-						PamItemType::AuthTok => Ok(PamItem::AuthTok(s)),
-						PamItemType::OldAuthTok => Ok(PamItem::OldAuthTok(s)),
-						_ => panic!("Unexpected PamItemType."),
+			PamItemType::FailDelay => PamItem::FailDelay(match item.is_null() {
+				true => None,
+				// SAFETY: Item is a function pointer of this type.
+				false => Some(unsafe { mem::transmute::<*const (), PamDelayFn>(item) }),
+			}),
+			PamItemType::XAuthData => {
+				let mut xauth = PamXAuthData::default();
+				// SAFETY: Pointers are valid for the given lengths when non-null.
+				if let Some(x) = unsafe { (item as *const sys::pam_xauth_data).as_ref() } {
+					if !x.name.is_null() && x.namelen > 0 {
+						let name = unsafe { std::slice::from_raw_parts(x.name as *const u8, x.namelen as usize) };
+						xauth.name = String::from_utf8_lossy(name).into_owned();
+					}
+					if !x.data.is_null() && x.datalen > 0 {
+						xauth.data = unsafe { std::slice::from_raw_parts(x.data as *const u8, x.datalen as usize) }.to_vec();
 					}
-				} else {
-					Err(res)
 				}
-			}
-		}
+
+				PamItem::XAuthData(xauth)
+			},
+		})
 	}
 
 	/// Sets the [PamItem] value associated with this handle.
 	///
-	// TODO: Logic for conv function
+	/// Fails with [PamResult::BadItem] if a string contains a null byte, or for
+	///  a [PamItem::FailDelay] function (see [PamHandle::pam_set_fail_delay]).
+	///
+	/// Setting a conversation also replaces the `PAM_FAIL_DELAY` item with that
+	///  of the conversation (if any), since the previous one expects the
+	///  previous conversation data.
 	pub fn pam_set_item(&mut self, item: PamItem) -> Result<()> {
+		let item_type = PamItemType::from(&item) as i32;
 		match item {
 			PamItem::Service(ref s)
 			| PamItem::User(ref s)
@@ -280,22 +563,54 @@ impl<'d> PamHandle<'d> {
 			| PamItem::Ruser(ref s)
 			| PamItem::Rhost(ref s)
 			| PamItem::AuthTok(ref s)
-			| PamItem::OldAuthTok(ref s) => {
-				let s_cstr = CString::new(&s[..]).unwrap();
-				let res = unsafe { self.ret(
-					sys::pam_set_item(self.0, PamItemType::from(item) as i32, s_cstr.as_ptr() as _)
-				)};
-
-				if res == PamResult::Success {
-					Ok(())
-				} else {
-					Err(res)
-				}
+			| PamItem::OldAuthTok(ref s)
+			| PamItem::XDisplay(ref s)
+			| PamItem::AuthTokType(ref s) => {
+				let s_cstr = CString::new(&s[..]).map_err(|_| PamResult::BadItem)?;
+				let rv = unsafe { sys::pam_set_item(self.0, item_type, s_cstr.as_ptr() as _) };
+				self.ret_unit(rv)
+			},
+			PamItem::Conv(conv) => {
+				let rv = unsafe { sys::pam_set_item(self.0, item_type, &conv.conv as *const _ as _) };
+				self.ret_unit(rv)?;
+
+				let delay = conv.delay.map_or(ptr::null(), |f| f as *const ());
+				self.3.push(conv);
+				let rv = unsafe { sys::pam_set_item(self.0, sys::PAM_FAIL_DELAY, delay) };
+				self.ret_unit(rv)
+			},
+			PamItem::FailDelay(Some(_)) => Err(PamResult::BadItem),
+			// SAFETY: Clearing the item is always valid.
+			PamItem::FailDelay(None) => unsafe { self.pam_set_fail_delay(None) },
+			PamItem::XAuthData(ref xauth) => {
+				// PAM copies the name as a C-string, and data by length.
+				let name = CString::new(&xauth.name[..]).map_err(|_| PamResult::BadItem)?;
+				let c_xauth = sys::pam_xauth_data {
+					namelen: xauth.name.len() as i32,
+					name: name.as_ptr() as *mut _,
+					datalen: xauth.data.len() as i32,
+					data: xauth.data.as_ptr() as *mut _,
+				};
+				let rv = unsafe { sys::pam_set_item(self.0, item_type, &c_xauth as *const _ as _) };
+				self.ret_unit(rv)
 			},
-			PamItem::Conv(ref _c) => todo!(),
 		}
 	}
 
+	/// Sets the raw `PAM_FAIL_DELAY` item, called in place of the failure delay.
+	///
+	/// [PamConv::fail_delay] sets a Rust callback safely, along with the
+	///  conversation.
+	///
+	/// # Safety
+	/// `delay` must be valid to call with the `appdata_ptr` of the current (and
+	///  any later) conversation of this handle.
+	pub unsafe fn pam_set_fail_delay(&mut self, delay: Option<PamDelayFn>) -> Result<()> {
+		let delay = delay.map_or(ptr::null(), |f| f as *const ());
+		let rv = unsafe { sys::pam_set_item(self.0, sys::PAM_FAIL_DELAY, delay) };
+		self.ret_unit(rv)
+	}
+
 	/// Gets the username passed to `pam_start(3)`, or [pam_get_item] with [PamItemType::User], or prompts the user.
 	///
 	// TODO: Return str/OsStr/CStr instead?
@@ -316,11 +631,14 @@ impl<'d> PamHandle<'d> {
 		}
 	}
 
+	/// Send a message through the conversation, returning the response.
+	///
+	/// The prompt is sent as is, not as a format string.
 	pub fn pam_prompt(&mut self, conv_type: PamConvType, prompt: &str) -> Result<String> {
 		let c_prompt = CString::new(prompt).unwrap();
 		let mut resp: MaybeUninit<*const ffi::c_char> = MaybeUninit::uninit();
 		let res = unsafe { self.ret(
-			sys::pam_prompt(self.0, conv_type as i32, resp.as_mut_ptr() as _, c_prompt.as_ptr())
+			sys::pam_prompt(self.0, conv_type as i32, resp.as_mut_ptr() as _, c"%s".as_ptr(), c_prompt.as_ptr())
 		)};
 
 		if res == PamResult::Success {
@@ -340,6 +658,14 @@ impl<'d> PamHandle<'d> {
 		}
 	}
 
+	/// Log a message to syslog, prefixed with the module and service names.
+	///
+	/// Null bytes are removed from `msg`.
+	pub fn pam_syslog<S: AsRef<str>>(&self, level: PamLogLevel, msg: S) {
+		let c_msg = CString::new(msg.as_ref().replace('\0', "")).unwrap_or_default();
+		unsafe { sys::pam_syslog(self.0, level as i32, c"%s".as_ptr(), c_msg.as_ptr()) }
+	}
+
 	/// Cleanup function for [sys::pam_set_data].
 	///
 	/// Simply re-boxes data, then drops.
@@ -0,0 +1,177 @@
+//! Fake PAM stack, for testing modules without installing them.
+//!
+//! With the `mock` feature, this crate uses an in-process fake of libpam
+//!  instead of linking to it. [MockPam] holds a fake handle, and calls the
+//!  `pam_sm_*` functions of a module linked into the test (e.g. by the module
+//!  crate enabling the feature for its dev-dependency on this crate).
+//!
+//! The conversation answers prompts from a script of responses, and records
+//!  every message for later assertions.
+//!
+//! Application transaction calls (e.g. [PamHandle::pam_authenticate]) return
+//!  the results given to [MockPam::results], since there is no module stack to
+//!  call, and fail with [PamResult::SystemErr] once those run out.
+
+use crate::{sys, PamConv, PamConvType, PamFlags, PamHandle, PamItem, PamLogLevel, PamMessage, PamResult};
+
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ffi::{self, CString};
+use std::{marker, ptr};
+use std::rc::Rc;
+use std::time::Duration;
+
+pub(crate) mod libpam;
+
+/// Signature of the `pam_sm_*` functions exported by a module.
+pub type SmFn = unsafe extern "C" fn(
+	pamh: *const (),
+	flags: ffi::c_int,
+	argc: ffi::c_int,
+	argv: *const *const ffi::c_char
+) -> ffi::c_int;
+
+/// Scripted conversation state, shared with [MockPam].
+#[derive(Debug, Default)]
+struct Script {
+	responses: VecDeque<String>,
+	messages: Vec<(PamConvType, String)>,
+}
+
+/// Answer prompts from the script, failing if it runs out.
+fn scripted_conv(msgs: &[PamMessage<'_>], resps: &mut [String], script: &mut Rc<RefCell<Script>>) -> PamResult {
+	let mut script = script.borrow_mut();
+	for (msg, resp) in msgs.iter().zip(resps.iter_mut()) {
+		script.messages.push((msg.style, msg.msg.to_string()));
+
+		if matches!(msg.style, PamConvType::PromptEchoOn | PamConvType::PromptEchoOff) {
+			match script.responses.pop_front() {
+				Some(r) => *resp = r,
+				None => return PamResult::ConvErr,
+			}
+		}
+	}
+
+	PamResult::Success
+}
+
+/// Fake PAM transaction, for calling module functions. See the [module](self) docs.
+pub struct MockPam {
+	pamh: PamHandle<'static>,
+	script: Rc<RefCell<Script>>,
+}
+
+impl MockPam {
+	/// Start a transaction for `service`, with no user set and an empty
+	///  conversation script.
+	pub fn new(service: &str) -> Self {
+		let script = Rc::new(RefCell::new(Script::default()));
+		let conv = PamConv::new(scripted_conv, script.clone());
+
+		let c_service = CString::new(service).expect("Service should not contain null bytes.");
+		let mut pamh: *const () = ptr::null();
+		// SAFETY: Arguments are valid, and the conversation is kept with the handle.
+		let rv = unsafe { libpam::pam_start(c_service.as_ptr(), ptr::null(), &conv.conv, &mut pamh) };
+		assert_eq!(rv, sys::PAM_SUCCESS);
+
+		Self {
+			pamh: PamHandle(pamh, PamResult::Success, marker::PhantomData, vec![conv]),
+			script,
+		}
+	}
+
+	/// Set the user, as if given to `pam_start(3)`.
+	pub fn user(self, user: &str) -> Self {
+		self.item(PamItem::User(user.to_string()))
+	}
+
+	/// Set an item. Panics if the item is invalid.
+	///
+	/// Setting [PamItem::Conv] replaces the scripted conversation.
+	pub fn item(mut self, item: PamItem) -> Self {
+		self.pamh.pam_set_item(item).expect("Item should be valid.");
+		self
+	}
+
+	/// Add responses to the script, each answering the next prompt.
+	pub fn responses<I, S>(self, responses: I) -> Self
+	where
+		I: IntoIterator<Item = S>,
+		S: Into<String>,
+	{
+		self.script.borrow_mut().responses.extend(responses.into_iter().map(Into::into));
+		self
+	}
+
+	/// Add results for application transaction calls (e.g.
+	///  [PamHandle::pam_authenticate]) to return, in order.
+	pub fn results<I>(self, results: I) -> Self
+	where
+		I: IntoIterator<Item = PamResult>,
+	{
+		// SAFETY: Handle is valid while self is.
+		unsafe { libpam::push_results(self.pamh.0, results.into_iter().map(|r| r as i32)) };
+		self
+	}
+
+	/// Call a module function, e.g. `pam_sm_authenticate`.
+	///
+	/// Panics if the module returns an unknown result code.
+	pub fn call(&mut self, f: SmFn, flags: PamFlags, args: &[&str]) -> PamResult {
+		let c_args: Vec<CString> = args.iter()
+			.map(|a| CString::new(*a).expect("Arguments should not contain null bytes."))
+			.collect();
+		let argv: Vec<*const ffi::c_char> = c_args.iter().map(|a| a.as_ptr()).collect();
+
+		// SAFETY: Handle and arguments are valid for the duration of the call.
+		let rv = unsafe { f(self.pamh.0, flags.bits(), argv.len() as i32, argv.as_ptr()) };
+		let res = PamResult::try_from(rv).unwrap_or_else(|_| panic!("Module returned unknown result {rv}."));
+		self.pamh.1 = res;
+
+		res
+	}
+
+	/// Handle given to modules, e.g. to inspect items and data after a call.
+	pub fn handle(&mut self) -> &mut PamHandle<'static> {
+		&mut self.pamh
+	}
+
+	/// Every message sent through the scripted conversation, in order.
+	pub fn messages(&self) -> Vec<(PamConvType, String)> {
+		self.script.borrow().messages.clone()
+	}
+
+	/// Every message logged with [PamHandle::pam_syslog], in order.
+	pub fn logs(&self) -> Vec<(PamLogLevel, String)> {
+		// SAFETY: Handle is valid while self is.
+		unsafe { libpam::logs(self.pamh.0) }.iter()
+			.filter_map(|(level, msg)| Some((PamLogLevel::try_from(*level).ok()?, msg.clone())))
+			.collect()
+	}
+
+	/// Every application transaction call, by function name, with its flags.
+	pub fn transactions(&self) -> Vec<(&'static str, PamFlags)> {
+		// SAFETY: Handle is valid while self is.
+		unsafe { libpam::transactions(self.pamh.0) }.iter()
+			.map(|(name, flags)| (*name, PamFlags::from_bits_retain(*flags)))
+			.collect()
+	}
+
+	/// Result of the last call on the handle, as given to `pam_end(3)`.
+	pub fn last_result(&self) -> PamResult {
+		self.pamh.1
+	}
+
+	/// Call the `PAM_FAIL_DELAY` item, as libpam does in place of the delay
+	///  after a failed transaction call. Returns whether the item was set.
+	pub fn fail_delay(&mut self, status: PamResult, delay: Duration) -> bool {
+		let usec = delay.as_micros().try_into().unwrap_or(u32::MAX);
+		// SAFETY: Handle is valid while self is, and the item is set by [PamHandle::pam_set_item].
+		unsafe { libpam::fail_delay(self.pamh.0, status as i32, usec) }
+	}
+
+	/// Number of scripted responses not yet used.
+	pub fn pending_responses(&self) -> usize {
+		self.script.borrow().responses.len()
+	}
+}
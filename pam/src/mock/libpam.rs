@@ -0,0 +1,398 @@
+//! In-process stand-ins for the libpam functions used by this crate.
+//!
+//! The handle is a boxed [MockState]. Behaviour follows Linux-PAM where it
+//!  matters to modules, e.g. items are copied, and the user is prompted for
+//!  through the conversation if unset. Application and module calls are not
+//!  distinguished, so (e.g.) tests may set the authentication token items.
+
+use crate::sys::{self, pam_conv, pam_message, pam_response, pam_xauth_data};
+use crate::{PamDelayFn, PamResult};
+
+use std::collections::{HashMap, VecDeque};
+use std::ffi::{c_char, CStr, CString};
+use std::{mem, ptr};
+
+type Cleanup = extern "C" fn(pamh: *const (), data: *mut (), error_status: i32);
+
+/// Handle state, owning everything returned to callers.
+pub(crate) struct MockState {
+	/// String items, by item type.
+	strings: HashMap<i32, CString>,
+	conv: Option<pam_conv>,
+	fail_delay: *const (),
+	xauth: Option<XAuth>,
+	data: HashMap<CString, (*mut (), Cleanup)>,
+	/// Environment, as `NAME=value` entries.
+	env: Vec<CString>,
+	/// Last description from [pam_strerror].
+	error: CString,
+	/// Messages from [pam_syslog], with priority.
+	logs: Vec<(i32, String)>,
+	/// Results for the next application transaction calls.
+	results: VecDeque<i32>,
+	/// Application transaction calls made, by name, with their flags.
+	transactions: Vec<(&'static str, i32)>,
+}
+
+/// Copy of the `PAM_XAUTHDATA` item, with the struct pointing into it.
+struct XAuth {
+	raw: pam_xauth_data,
+	/// Name and data pointed to by `raw`.
+	_owned: (CString, Vec<u8>),
+}
+
+const STRING_ITEMS: [i32; 10] = [
+	sys::PAM_SERVICE,
+	sys::PAM_USER,
+	sys::PAM_TTY,
+	sys::PAM_RHOST,
+	sys::PAM_AUTHTOK,
+	sys::PAM_OLDAUTHTOK,
+	sys::PAM_RUSER,
+	sys::PAM_USER_PROMPT,
+	sys::PAM_XDISPLAY,
+	sys::PAM_AUTHTOK_TYPE,
+];
+
+unsafe fn state<'a>(pamh: *const ()) -> Option<&'a mut MockState> {
+	(pamh as *mut MockState).as_mut()
+}
+
+/// Send a single message, returning the (possibly null) malloc'd response.
+unsafe fn converse(conv: Option<pam_conv>, style: i32, text: *const c_char) -> Result<*mut c_char, i32> {
+	let conv = conv.ok_or(sys::PAM_CONV_ERR)?;
+	let msg = pam_message { msg_style: style, msg: text };
+	let msgs = [&msg as *const pam_message];
+
+	let mut resp: *mut pam_response = ptr::null_mut();
+	match (conv.conv)(1, msgs.as_ptr(), &mut resp, conv.appdata_ptr) {
+		sys::PAM_SUCCESS if resp.is_null() => Err(sys::PAM_CONV_ERR),
+		sys::PAM_SUCCESS => {
+			let text = (*resp).resp;
+			libc::free(resp as _);
+			Ok(text)
+		},
+		rv => Err(rv),
+	}
+}
+
+pub unsafe fn pam_start(
+	service_name: *const c_char,
+	user: *const c_char,
+	pam_conversation: *const pam_conv,
+	pamh: *mut *const ()
+) -> i32 {
+	if service_name.is_null() || pam_conversation.is_null() || pamh.is_null() {
+		return sys::PAM_SYSTEM_ERR
+	}
+
+	let mut state = Box::new(MockState {
+		strings: HashMap::new(),
+		conv: Some(*pam_conversation),
+		fail_delay: ptr::null(),
+		xauth: None,
+		data: HashMap::new(),
+		env: Vec::new(),
+		error: CString::default(),
+		logs: Vec::new(),
+		results: VecDeque::new(),
+		transactions: Vec::new(),
+	});
+	state.strings.insert(sys::PAM_SERVICE, CStr::from_ptr(service_name).into());
+	if !user.is_null() {
+		state.strings.insert(sys::PAM_USER, CStr::from_ptr(user).into());
+	}
+
+	*pamh = Box::into_raw(state) as *const ();
+	sys::PAM_SUCCESS
+}
+
+pub unsafe fn pam_end(pamh: *const (), pam_status: i32) -> i32 {
+	if pamh.is_null() {
+		return sys::PAM_SYSTEM_ERR
+	}
+
+	let state = Box::from_raw(pamh as *mut MockState);
+	for (data, cleanup) in state.data.values() {
+		cleanup(pamh, *data, pam_status);
+	}
+
+	sys::PAM_SUCCESS
+}
+
+pub unsafe fn pam_get_user(pamh: *const (), user: *mut *const c_char, prompt: *const c_char) -> i32 {
+	let (Some(state), false) = (state(pamh), user.is_null()) else {
+		return sys::PAM_SYSTEM_ERR
+	};
+
+	if !state.strings.contains_key(&sys::PAM_USER) {
+		let prompt = match (prompt.is_null(), state.strings.get(&sys::PAM_USER_PROMPT)) {
+			(false, _) => prompt,
+			(true, Some(p)) => p.as_ptr(),
+			(true, None) => c"login: ".as_ptr(),
+		};
+
+		let resp = match converse(state.conv, sys::PAM_PROMPT_ECHO_ON, prompt) {
+			Ok(r) if r.is_null() => return sys::PAM_CONV_ERR,
+			Ok(r) => r,
+			Err(rv) => return rv,
+		};
+		state.strings.insert(sys::PAM_USER, CStr::from_ptr(resp).into());
+		libc::free(resp as _);
+	}
+
+	*user = state.strings[&sys::PAM_USER].as_ptr();
+	sys::PAM_SUCCESS
+}
+
+pub unsafe fn pam_get_item(pamh: *const (), item_type: i32, item: *mut *const ()) -> i32 {
+	let (Some(state), false) = (state(pamh), item.is_null()) else {
+		return sys::PAM_SYSTEM_ERR
+	};
+
+	*item = match item_type {
+		sys::PAM_CONV => state.conv.as_ref().map_or(ptr::null(), |c| c as *const _ as _),
+		sys::PAM_FAIL_DELAY => state.fail_delay,
+		sys::PAM_XAUTHDATA => state.xauth.as_ref().map_or(ptr::null(), |x| &x.raw as *const _ as _),
+		t if STRING_ITEMS.contains(&t) => state.strings.get(&t).map_or(ptr::null(), |s| s.as_ptr() as _),
+		_ => return sys::PAM_BAD_ITEM,
+	};
+
+	sys::PAM_SUCCESS
+}
+
+pub unsafe fn pam_set_item(pamh: *const (), item_type: i32, item: *const ()) -> i32 {
+	let Some(state) = state(pamh) else {
+		return sys::PAM_SYSTEM_ERR
+	};
+
+	match item_type {
+		sys::PAM_CONV => match (item as *const pam_conv).as_ref() {
+			Some(conv) => state.conv = Some(*conv),
+			None => return sys::PAM_PERM_DENIED,
+		},
+		sys::PAM_FAIL_DELAY => state.fail_delay = item,
+		sys::PAM_XAUTHDATA => {
+			state.xauth = (item as *const pam_xauth_data).as_ref().map(|x| {
+				let name = match x.name.is_null() {
+					true => CString::default(),
+					false => CStr::from_ptr(x.name).into(),
+				};
+				let data = match x.data.is_null() || x.datalen <= 0 {
+					true => Vec::new(),
+					false => std::slice::from_raw_parts(x.data as *const u8, x.datalen as usize).to_vec(),
+				};
+
+				let raw = pam_xauth_data {
+					namelen: name.as_bytes().len() as i32,
+					name: name.as_ptr() as *mut _,
+					datalen: data.len() as i32,
+					data: data.as_ptr() as *mut _,
+				};
+				XAuth { raw, _owned: (name, data) }
+			});
+		},
+		t if STRING_ITEMS.contains(&t) => {
+			match item.is_null() {
+				true => state.strings.remove(&t),
+				false => state.strings.insert(t, CStr::from_ptr(item as _).into()),
+			};
+		},
+		_ => return sys::PAM_BAD_ITEM,
+	}
+
+	sys::PAM_SUCCESS
+}
+
+pub unsafe fn pam_get_data(pamh: *const (), module_data_name: *const c_char, data: *mut *const ()) -> i32 {
+	let (Some(state), false, false) = (state(pamh), module_data_name.is_null(), data.is_null()) else {
+		return sys::PAM_SYSTEM_ERR
+	};
+
+	match state.data.get(CStr::from_ptr(module_data_name)) {
+		Some((d, _)) => {
+			*data = *d;
+			sys::PAM_SUCCESS
+		},
+		None => sys::PAM_NO_MODULE_DATA,
+	}
+}
+
+pub unsafe fn pam_set_data(
+	pamh: *const (),
+	module_data_name: *const c_char,
+	data: *const (),
+	cleanup: Cleanup
+) -> i32 {
+	let (Some(state), false) = (state(pamh), module_data_name.is_null()) else {
+		return sys::PAM_SYSTEM_ERR
+	};
+
+	let old = state.data.insert(CStr::from_ptr(module_data_name).into(), (data as *mut (), cleanup));
+	if let Some((old, old_cleanup)) = old {
+		old_cleanup(pamh, old, sys::PAM_DATA_REPLACE);
+	}
+
+	sys::PAM_SUCCESS
+}
+
+/// Index of the environment entry for `name`.
+fn env_index(env: &[CString], name: &[u8]) -> Option<usize> {
+	env.iter().position(|e| e.as_bytes().strip_prefix(name).is_some_and(|v| v.first() == Some(&b'=')))
+}
+
+pub unsafe fn pam_getenv(pamh: *const (), name: *const c_char) -> *const c_char {
+	let (Some(state), false) = (state(pamh), name.is_null()) else {
+		return ptr::null()
+	};
+
+	let name = CStr::from_ptr(name).to_bytes();
+	match env_index(&state.env, name) {
+		Some(i) => state.env[i].as_ptr().add(name.len() + 1),
+		None => ptr::null(),
+	}
+}
+
+pub unsafe fn pam_getenvlist(pamh: *const ()) -> *mut *mut c_char {
+	let Some(state) = state(pamh) else {
+		return ptr::null_mut()
+	};
+
+	let list = libc::calloc(state.env.len() + 1, std::mem::size_of::<*mut c_char>()) as *mut *mut c_char;
+	if list.is_null() {
+		return list
+	}
+	for (i, entry) in state.env.iter().enumerate() {
+		*list.add(i) = libc::strdup(entry.as_ptr());
+	}
+
+	list
+}
+
+pub unsafe fn pam_putenv(pamh: *const (), name_value: *const c_char) -> i32 {
+	let (Some(state), false) = (state(pamh), name_value.is_null()) else {
+		return sys::PAM_PERM_DENIED
+	};
+
+	let entry = CStr::from_ptr(name_value);
+	let name = entry.to_bytes().split(|&b| b == b'=').next().unwrap_or_default();
+	if name.is_empty() {
+		return sys::PAM_BAD_ITEM
+	}
+
+	// Without a value, the variable is deleted.
+	match (env_index(&state.env, name), name.len() < entry.to_bytes().len()) {
+		(Some(i), true) => state.env[i] = entry.into(),
+		(None, true) => state.env.push(entry.into()),
+		(Some(i), false) => _ = state.env.remove(i),
+		(None, false) => return sys::PAM_BAD_ITEM,
+	}
+
+	sys::PAM_SUCCESS
+}
+
+pub unsafe fn pam_strerror(pamh: *const (), errnum: i32) -> *const c_char {
+	let Some(state) = state(pamh) else {
+		return ptr::null()
+	};
+
+	let desc = match PamResult::try_from(errnum) {
+		Ok(r) => format!("{r:?}"),
+		Err(_) => format!("Unknown PAM error {errnum}"),
+	};
+	state.error = CString::new(desc).unwrap_or_default();
+
+	state.error.as_ptr()
+}
+
+/// Unlike libpam, takes only the message (as for a `"%s"` format).
+pub unsafe fn pam_prompt(pamh: *const (), style: i32, response: *mut *const c_char, _fmt: *const c_char, msg: *const c_char) -> i32 {
+	let Some(state) = state(pamh) else {
+		return sys::PAM_SYSTEM_ERR
+	};
+
+	match converse(state.conv, style, msg) {
+		Ok(resp) => {
+			if !response.is_null() {
+				*response = resp;
+			} else if !resp.is_null() {
+				libc::free(resp as _);
+			}
+			sys::PAM_SUCCESS
+		},
+		Err(rv) => rv,
+	}
+}
+
+/// Unlike libpam, takes only the message (as for a `"%s"` format).
+pub unsafe fn pam_syslog(pamh: *const (), priority: i32, _fmt: *const c_char, msg: *const c_char) {
+	if let (Some(state), false) = (state(pamh), msg.is_null()) {
+		state.logs.push((priority, CStr::from_ptr(msg).to_string_lossy().into_owned()));
+	}
+}
+
+/// Call the `PAM_FAIL_DELAY` item with the conversation data, as libpam does
+///  instead of sleeping. Returns whether the item was set.
+pub(crate) unsafe fn fail_delay(pamh: *const (), status: i32, usec: u32) -> bool {
+	let Some(state) = state(pamh) else {
+		return false
+	};
+	if state.fail_delay.is_null() {
+		return false
+	}
+
+	let delay = mem::transmute::<*const (), PamDelayFn>(state.fail_delay);
+	let appdata = state.conv.map_or(ptr::null(), |c| c.appdata_ptr);
+	delay(status, usec, appdata);
+
+	true
+}
+
+/// Messages logged with [pam_syslog].
+pub(crate) unsafe fn logs<'a>(pamh: *const ()) -> &'a [(i32, String)] {
+	state(pamh).map_or(&[], |s| &s.logs)
+}
+
+/// Add results for the next application transaction calls to return.
+pub(crate) unsafe fn push_results(pamh: *const (), results: impl IntoIterator<Item = i32>) {
+	if let Some(state) = state(pamh) {
+		state.results.extend(results);
+	}
+}
+
+/// Application transaction calls made, by name, with their flags.
+pub(crate) unsafe fn transactions<'a>(pamh: *const ()) -> &'a [(&'static str, i32)] {
+	state(pamh).map_or(&[], |s| &s.transactions)
+}
+
+/// Record a transaction call, returning the next scripted result. Without a
+///  module stack to call, fails once the results run out.
+unsafe fn transaction(pamh: *const (), name: &'static str, flags: i32) -> i32 {
+	let Some(state) = state(pamh) else {
+		return sys::PAM_SYSTEM_ERR
+	};
+
+	state.transactions.push((name, flags));
+	state.results.pop_front().unwrap_or(sys::PAM_SYSTEM_ERR)
+}
+
+/* Application transaction calls, which need a module stack.
+*/
+pub unsafe fn pam_authenticate(pamh: *const (), flags: i32) -> i32 {
+	transaction(pamh, "pam_authenticate", flags)
+}
+pub unsafe fn pam_setcred(pamh: *const (), flags: i32) -> i32 {
+	transaction(pamh, "pam_setcred", flags)
+}
+pub unsafe fn pam_acct_mgmt(pamh: *const (), flags: i32) -> i32 {
+	transaction(pamh, "pam_acct_mgmt", flags)
+}
+pub unsafe fn pam_open_session(pamh: *const (), flags: i32) -> i32 {
+	transaction(pamh, "pam_open_session", flags)
+}
+pub unsafe fn pam_close_session(pamh: *const (), flags: i32) -> i32 {
+	transaction(pamh, "pam_close_session", flags)
+}
+pub unsafe fn pam_chauthtok(pamh: *const (), flags: i32) -> i32 {
+	transaction(pamh, "pam_chauthtok", flags)
+}
@@ -0,0 +1,39 @@
+//! Module options, as given after the module path in the PAM configuration.
+//!
+//! Options are either flags (`debug`) or name and value pairs (`timeout=30`).
+//!  Usually [PamOptions] is derived for a struct implementing `Default`, with a
+//!  field per option:
+//!  - `bool` fields are flags.
+//!  - `Option<T>` and other fields take a value, parsed with `FromStr`.
+//!
+//! Taking `&T` in place of the arguments slice (e.g. with
+//!  `#[authenticate]`) makes the module function parse them into `T`. Invalid
+//!  options are logged with `pam_syslog(3)`, and fail with
+//!  [PamResult::ServiceErr](crate::PamResult::ServiceErr).
+
+use std::ffi::CStr;
+use std::fmt;
+
+/// Module options, parsed from the module arguments.
+pub trait PamOptions: Sized {
+	/// Parse all arguments, failing on the first invalid one.
+	fn parse(args: &[&CStr]) -> Result<Self, OptionError>;
+}
+
+/// Argument rejected by [PamOptions::parse].
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum OptionError {
+	/// Option not known to the module.
+	Unknown(String),
+	/// Known option with a missing, unexpected, or unparsable value.
+	Invalid(String),
+}
+impl fmt::Display for OptionError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Unknown(arg) => write!(f, "Unknown option \"{arg}\""),
+			Self::Invalid(arg) => write!(f, "Invalid option \"{arg}\""),
+		}
+	}
+}
+impl std::error::Error for OptionError {}
@@ -52,6 +52,28 @@ pub const PAM_XDISPLAY: i32 = 11;
 pub const PAM_XAUTHDATA: i32 = 12;
 pub const PAM_AUTHTOK_TYPE: i32 = 13;
 
+/* Flags
+*/
+pub const PAM_SILENT: i32 = 0x8000;
+pub const PAM_DISALLOW_NULL_AUTHTOK: i32 = 0x0001;
+pub const PAM_CHANGE_EXPIRED_AUTHTOK: i32 = 0x0020;
+// Module only, for pam_sm_chauthtok().
+pub const PAM_PRELIM_CHECK: i32 = 0x4000;
+pub const PAM_UPDATE_AUTHTOK: i32 = 0x2000;
+
+/* pam_setcred() actions
+*/
+pub const PAM_ESTABLISH_CRED: i32 = 0x0002;
+pub const PAM_DELETE_CRED: i32 = 0x0004;
+pub const PAM_REINITIALIZE_CRED: i32 = 0x0008;
+pub const PAM_REFRESH_CRED: i32 = 0x0010;
+
+/// Cleanup status when data is replaced by `pam_set_data(3)`.
+pub const PAM_DATA_REPLACE: i32 = 0x20000000;
+
+/// Maximum number of messages in a single conversation.
+pub const PAM_MAX_NUM_MSG: i32 = 32;
+
 /* Conversation types
 */
 pub const PAM_PROMPT_ECHO_OFF: i32 = 1;
@@ -84,6 +106,20 @@ pub struct pam_conv {
 	pub appdata_ptr: *const (),
 }
 
+/// Linux-PAM `PAM_XAUTHDATA` item.
+#[repr(C)]
+pub struct pam_xauth_data {
+	pub namelen: c_int,
+	pub name: *mut c_char,
+	pub datalen: c_int,
+	pub data: *mut c_char,
+}
+
+/// With the `mock` feature, stand-ins are used instead of libpam.
+#[cfg(feature = "mock")]
+pub use crate::mock::libpam::*;
+
+#[cfg(not(feature = "mock"))]
 #[link(name = "pam")]
 extern "C" {
 	pub fn pam_start(
@@ -108,14 +144,26 @@ extern "C" {
 	) -> i32;
 
 	pub fn pam_getenv(pamh: *const (), name: *const c_char) -> *const c_char;
-	pub fn pam_getenvlist(pamh: *const ()) -> *const *const c_char;
+	/// Array and strings are malloc'd, and must be freed by the caller.
+	pub fn pam_getenvlist(pamh: *const ()) -> *mut *mut c_char;
 
 	pub fn pam_get_authtok(pamh: *const (), item: i32, authtok: *mut *const c_char, prompt: *const c_char) -> i32;
 	pub fn pam_get_authtok_noverify(pamh: *const (), item: i32, authtok: *mut *const c_char, prompt: *const c_char) -> i32;
 	pub fn pam_get_authtok_verify(pamh: *const (), item: i32, authtok: *mut *const c_char, prompt: *const c_char) -> i32;
 
+	pub fn pam_authenticate(pamh: *const (), flags: i32) -> i32;
 	pub fn pam_setcred(pamh: *const (), flags: i32) -> i32;
+	pub fn pam_acct_mgmt(pamh: *const (), flags: i32) -> i32;
+	pub fn pam_open_session(pamh: *const (), flags: i32) -> i32;
+	pub fn pam_close_session(pamh: *const (), flags: i32) -> i32;
+	pub fn pam_chauthtok(pamh: *const (), flags: i32) -> i32;
+
+	pub fn pam_putenv(pamh: *const (), name_value: *const c_char) -> i32;
+	/// Returns a pointer to a static or handle-owned string.
+	pub fn pam_strerror(pamh: *const (), errnum: i32) -> *const c_char;
 
 	/// Linux-PAM extension.
 	pub fn pam_prompt(pamh: *const (), style: i32, response: *mut *const c_char, fmt: *const c_char, ...) -> i32;
+	/// Linux-PAM extension.
+	pub fn pam_syslog(pamh: *const (), priority: c_int, fmt: *const c_char, ...);
 }
@@ -2,6 +2,8 @@ use crate::PamConv;
 use crate::macros::map_enum_i32;
 use crate::sys;
 
+use bitflags::bitflags;
+
 map_enum_i32! (
 	/// PAM result/return codes.
 	///
@@ -68,12 +70,15 @@ map_enum_i32!(
 	// Linux-PAM extensions
 	/// Function pointer to redirect centrally managed failure delays.
 	FailDelay => sys::PAM_FAIL_DELAY,
+	/// Name of the X display, for graphical applications.
 	XDisplay => sys::PAM_XDISPLAY,
+	/// X server authentication data, for the `XDisplay` item.
 	XAuthData => sys::PAM_XAUTHDATA,
+	/// Word inserted into password prompts, e.g. "UNIX" in "New UNIX password: ".
 	AuthTokType => sys::PAM_AUTHTOK_TYPE,
 );
-impl From<PamItem> for PamItemType {
-	fn from(item: PamItem) -> Self {
+impl From<&PamItem> for PamItemType {
+	fn from(item: &PamItem) -> Self {
 		match item {
 			PamItem::Service(_) => PamItemType::Service,
 			PamItem::User(_) => PamItemType::User,
@@ -84,10 +89,69 @@ impl From<PamItem> for PamItemType {
 			PamItem::AuthTok(_) => PamItemType::AuthTok,
 			PamItem::OldAuthTok(_) => PamItemType::OldAuthTok,
 			PamItem::Conv(_) => PamItemType::Conv,
+			PamItem::FailDelay(_) => PamItemType::FailDelay,
+			PamItem::XDisplay(_) => PamItemType::XDisplay,
+			PamItem::XAuthData(_) => PamItemType::XAuthData,
+			PamItem::AuthTokType(_) => PamItemType::AuthTokType,
 		}
 	}
 }
 
+bitflags! {
+	/// Flags given to transaction calls, and to module functions.
+	#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+	pub struct PamFlags: i32 {
+		/// Do not send informational messages through the conversation.
+		const SILENT = sys::PAM_SILENT;
+		/// Fail authentication if the user has no authentication token.
+		const DISALLOW_NULL_AUTHTOK = sys::PAM_DISALLOW_NULL_AUTHTOK;
+		/// Set credentials, as with [PamCred::Establish].
+		const ESTABLISH_CRED = sys::PAM_ESTABLISH_CRED;
+		/// Delete credentials, as with [PamCred::Delete].
+		const DELETE_CRED = sys::PAM_DELETE_CRED;
+		/// Reinitialize credentials, as with [PamCred::Reinitialize].
+		const REINITIALIZE_CRED = sys::PAM_REINITIALIZE_CRED;
+		/// Refresh credentials, as with [PamCred::Refresh].
+		const REFRESH_CRED = sys::PAM_REFRESH_CRED;
+		/// Only change the authentication token if it has expired.
+		const CHANGE_EXPIRED_AUTHTOK = sys::PAM_CHANGE_EXPIRED_AUTHTOK;
+		/// First `pam_sm_chauthtok` pass, checking the token can be changed.
+		const PRELIM_CHECK = sys::PAM_PRELIM_CHECK;
+		/// Second `pam_sm_chauthtok` pass, changing the token.
+		const UPDATE_AUTHTOK = sys::PAM_UPDATE_AUTHTOK;
+	}
+}
+
+map_enum_i32!(
+	/// Actions for [PamHandle::pam_setcred](crate::PamHandle::pam_setcred).
+	///
+	PamCred,
+
+	/// Initialize the credentials for the user.
+	Establish => sys::PAM_ESTABLISH_CRED,
+	/// Delete the user's credentials.
+	Delete => sys::PAM_DELETE_CRED,
+	/// Fully reinitialize the user's credentials.
+	Reinitialize => sys::PAM_REINITIALIZE_CRED,
+	/// Extend the lifetime of existing credentials.
+	Refresh => sys::PAM_REFRESH_CRED,
+);
+
+map_enum_i32!(
+	/// Syslog priorities, for [PamHandle::pam_syslog](crate::PamHandle::pam_syslog).
+	///
+	PamLogLevel,
+
+	Emerg => libc::LOG_EMERG,
+	Alert => libc::LOG_ALERT,
+	Crit => libc::LOG_CRIT,
+	Error => libc::LOG_ERR,
+	Warn => libc::LOG_WARNING,
+	Notice => libc::LOG_NOTICE,
+	Info => libc::LOG_INFO,
+	Debug => libc::LOG_DEBUG,
+);
+
 map_enum_i32!(
 	/// All supported conversation types.
 	///
@@ -114,4 +178,25 @@ pub enum PamItem {
 	AuthTok(String),
 	OldAuthTok(String),
 	Conv(PamConv),
+	/// Called in place of the failure delay, with the `appdata_ptr` of the
+	///  conversation. See [PamConv::fail_delay] for a Rust callback.
+	///
+	/// Only `None` may be set with [PamHandle::pam_set_item](crate::PamHandle::pam_set_item).
+	///  See [PamHandle::pam_set_fail_delay](crate::PamHandle::pam_set_fail_delay).
+	FailDelay(Option<PamDelayFn>),
+	XDisplay(String),
+	XAuthData(PamXAuthData),
+	AuthTokType(String),
+}
+
+/// Raw `PAM_FAIL_DELAY` function, given the status, delay in microseconds, and
+///  `appdata_ptr` of the conversation.
+pub type PamDelayFn = unsafe extern "C" fn(i32, u32, *const ());
+
+/// X server authentication data, as in the `PAM_XAUTHDATA` item.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct PamXAuthData {
+	/// Authentication method, e.g. "MIT-MAGIC-COOKIE-1".
+	pub name: String,
+	pub data: Vec<u8>,
 }
\ No newline at end of file
@@ -0,0 +1,64 @@
+use pam::{PamConv, PamConvType, PamMessage, PamResult};
+use std::borrow::Cow;
+use std::rc::Rc;
+
+#[test]
+fn closure_conv() {
+	let conv = PamConv::new(|msgs, resps, calls: &mut u32| {
+		*calls += 1;
+		resps[0] = format!("{}{}", msgs[0].msg.to_uppercase(), calls);
+		PamResult::Success
+	}, 0);
+
+	assert_eq!(conv.call(PamConvType::PromptEchoOn, "login: ").as_deref(), Ok("LOGIN: 1"));
+	assert_eq!(conv.call(PamConvType::PromptEchoOff, "password: ").as_deref(), Ok("PASSWORD: 2"));
+
+	let conv = PamConv::new(|_, _, _: &mut ()| PamResult::ConvErr, ());
+	assert_eq!(conv.call(PamConvType::PromptEchoOn, "login: "), Err(PamResult::ConvErr));
+}
+
+#[test]
+fn conv_drops_data() {
+	let data = Rc::new(());
+	let captured = data.clone();
+	let conv = PamConv::new(move |_, _, _: &mut Rc<()>| {
+		let _ = &captured;
+		PamResult::Success
+	}, data.clone());
+	assert_eq!(Rc::strong_count(&data), 3);
+
+	drop(conv);
+	assert_eq!(Rc::strong_count(&data), 1);
+}
+
+#[test]
+fn multi_message_conv() {
+	let conv = PamConv::new(|msgs, resps, _: &mut ()| {
+		for (msg, resp) in msgs.iter().zip(resps.iter_mut()) {
+			if msg.style == PamConvType::PromptEchoOn {
+				*resp = msg.msg.trim_end_matches(": ").to_string();
+			}
+		}
+		PamResult::Success
+	}, ());
+
+	let msg = |style, msg| PamMessage { style, msg: Cow::Borrowed(msg) };
+	let msgs = [
+		msg(PamConvType::TextInfo, "Welcome"),
+		msg(PamConvType::PromptEchoOn, "first: "),
+		msg(PamConvType::PromptEchoOn, "second: "),
+	];
+	assert_eq!(conv.converse(&msgs), Ok(vec![String::new(), "first".into(), "second".into()]));
+
+	// Too many messages, or ones not representable in C, never reach the function.
+	let msgs = vec![msg(PamConvType::TextInfo, ""); 33];
+	assert_eq!(conv.converse(&msgs), Err(PamResult::ConvErr));
+	assert_eq!(conv.call(PamConvType::TextInfo, "a\0b"), Err(PamResult::ConvErr));
+	assert_eq!(conv.converse(&[]), Err(PamResult::ConvErr));
+}
+
+#[test]
+fn conv_panic() {
+	let conv = PamConv::new(|_, _, _: &mut ()| panic!("conversation failed"), ());
+	assert_eq!(conv.call(PamConvType::PromptEchoOn, "login: "), Err(PamResult::ConvErr));
+}
@@ -0,0 +1,209 @@
+#![cfg(feature = "mock")]
+
+use pam::mock::MockPam;
+use pam::*;
+use std::cell::{Cell, RefCell};
+use std::ffi::CStr;
+use std::rc::Rc;
+use std::time::Duration;
+
+#[authenticate]
+fn auth(pamh: &mut PamHandle, _flags: i32, args: &[&CStr]) -> PamResult {
+	let user = match pamh.pam_get_user("Username: ") {
+		Ok(u) => u,
+		Err(e) => return e,
+	};
+	let pass = match pamh.pam_prompt(PamConvType::PromptEchoOff, "Password: ") {
+		Ok(p) => p,
+		Err(e) => return e,
+	};
+
+	if args.contains(&c"verbose") {
+		_ = pamh.pam_prompt(PamConvType::TextInfo, &format!("Hello {user}"));
+	}
+
+	match (user.as_str(), pass.as_str()) {
+		("alice", "hunter2") => PamResult::Success,
+		_ => PamResult::AuthErr,
+	}
+}
+
+#[test]
+fn scripted_auth() {
+	let mut pam = MockPam::new("login").responses(["alice", "hunter2"]);
+	assert_eq!(pam.call(pam_sm_authenticate, PamFlags::empty(), &["verbose"]), PamResult::Success);
+	assert_eq!(pam.messages(), [
+		(PamConvType::PromptEchoOn, "Username: ".to_string()),
+		(PamConvType::PromptEchoOff, "Password: ".to_string()),
+		(PamConvType::TextInfo, "Hello alice".to_string()),
+	]);
+	assert!(matches!(pam.handle().pam_get_item(PamItemType::User), Ok(PamItem::User(u)) if u == "alice"));
+
+	// User is only prompted for if unset.
+	let mut pam = MockPam::new("login").user("bob").responses(["hunter2"]);
+	assert_eq!(pam.call(pam_sm_authenticate, PamFlags::empty(), &[]), PamResult::AuthErr);
+	assert_eq!(pam.messages().len(), 1);
+
+	// Running out of responses fails the conversation.
+	let mut pam = MockPam::new("login").user("alice");
+	assert_eq!(pam.call(pam_sm_authenticate, PamFlags::empty(), &[]), PamResult::ConvErr);
+
+	// Prompts are not format strings.
+	let mut pam = MockPam::new("login").user("%s%n").responses(["hunter2"]);
+	assert_eq!(pam.call(pam_sm_authenticate, PamFlags::empty(), &["verbose"]), PamResult::AuthErr);
+	assert_eq!(pam.messages()[1], (PamConvType::TextInfo, "Hello %s%n".to_string()));
+}
+
+#[test]
+fn handle_state() {
+	let mut pam = MockPam::new("login")
+		.item(PamItem::Tty("/dev/tty1".into()))
+		.item(PamItem::XAuthData(PamXAuthData { name: "MIT-MAGIC-COOKIE-1".into(), data: vec![0, 1, 2] }));
+	let pamh = pam.handle();
+	assert!(matches!(pamh.pam_get_item(PamItemType::Service), Ok(PamItem::Service(s)) if s == "login"));
+	assert!(matches!(pamh.pam_get_item(PamItemType::Tty), Ok(PamItem::Tty(s)) if s == "/dev/tty1"));
+	assert!(matches!(pamh.pam_get_item(PamItemType::XAuthData), Ok(PamItem::XAuthData(x)) if x.data == [0, 1, 2]));
+
+	pamh.pam_putenv("A=1").unwrap();
+	pamh.pam_putenv("B=2").unwrap();
+	pamh.pam_putenv("A").unwrap();
+	assert_eq!(pamh.pam_putenv("A"), Err(PamResult::BadItem));
+	assert_eq!(pamh.pam_getenvlist(), Ok(vec![("B".to_string(), "2".to_string())]));
+	assert_eq!(pamh.pam_getenv("B").as_deref(), Some("2"));
+	assert_eq!(pamh.pam_getenv("A"), None);
+	assert_eq!(pamh.pam_getenv("B\0"), None);
+
+	// Data is cleaned up when replaced, and when the transaction ends.
+	let data = Rc::new(());
+	pamh.pam_set_data("data", data.clone()).unwrap();
+	pamh.pam_set_data("data", data.clone()).unwrap();
+	assert_eq!(Rc::strong_count(&data), 2);
+	assert!(unsafe { pamh.pam_get_data::<Rc<()>, _>("data") }.is_ok());
+	assert_eq!(unsafe { pamh.pam_get_data::<Rc<()>, _>("none") }.err(), Some(PamResult::NoModuleData));
+	drop(pam);
+	assert_eq!(Rc::strong_count(&data), 1);
+}
+
+#[test]
+fn transactions() {
+	let mut pam = MockPam::new("login").results([PamResult::Success, PamResult::NewAuthTokReqd, PamResult::Success]);
+	let pamh = pam.handle();
+	assert_eq!(pamh.pam_authenticate(PamFlags::SILENT), Ok(()));
+	assert_eq!(pamh.pam_acct_mgmt(PamFlags::empty()), Err(PamResult::NewAuthTokReqd));
+	assert_eq!(pamh.pam_chauthtok(PamFlags::CHANGE_EXPIRED_AUTHTOK), Ok(()));
+	assert_eq!(pam.last_result(), PamResult::Success);
+
+	// Without a module stack, calls fail once the results run out.
+	let pamh = pam.handle();
+	assert_eq!(pamh.pam_setcred(PamCred::Establish, PamFlags::SILENT), Err(PamResult::SystemErr));
+	assert_eq!(pam.last_result(), PamResult::SystemErr);
+	let pamh = pam.handle();
+	assert_eq!(pamh.pam_open_session(PamFlags::empty()), Err(PamResult::SystemErr));
+	assert_eq!(pamh.pam_close_session(PamFlags::SILENT), Err(PamResult::SystemErr));
+
+	assert_eq!(pam.transactions(), [
+		("pam_authenticate", PamFlags::SILENT),
+		("pam_acct_mgmt", PamFlags::empty()),
+		("pam_chauthtok", PamFlags::CHANGE_EXPIRED_AUTHTOK),
+		("pam_setcred", PamFlags::ESTABLISH_CRED | PamFlags::SILENT),
+		("pam_open_session", PamFlags::empty()),
+		("pam_close_session", PamFlags::SILENT),
+	]);
+
+	let pamh = pam.handle();
+	assert_eq!(pamh.pam_strerror(PamResult::AuthErr), "AuthErr");
+	assert_eq!(pamh.pam_strerror(PamResult::Success), "Success");
+}
+
+#[open_session]
+fn open(_pamh: &mut PamHandle, _flags: i32, _args: &[&CStr]) -> PamResult {
+	panic!("session failed");
+}
+
+#[close_session(on_panic = PamResult::Ignore)]
+fn close(_pamh: &mut PamHandle, _flags: i32, _args: &[&CStr]) -> PamResult {
+	panic!("session failed");
+}
+
+#[test]
+fn module_panics() {
+	let mut pam = MockPam::new("login");
+	assert_eq!(pam.call(pam_sm_open_session, PamFlags::empty(), &[]), PamResult::SystemErr);
+	assert_eq!(pam.call(pam_sm_close_session, PamFlags::empty(), &[]), PamResult::Ignore);
+	assert_eq!(pam.logs(), [
+		(PamLogLevel::Error, "pam_sm_open_session panicked".to_string()),
+		(PamLogLevel::Error, "pam_sm_close_session panicked".to_string()),
+	]);
+}
+
+#[chauthtok]
+fn passwd(pamh: &mut PamHandle, flags: PamFlags, _args: &[&CStr]) -> PamResult {
+	if flags.contains(PamFlags::PRELIM_CHECK) {
+		return PamResult::Success
+	}
+
+	if flags.contains(PamFlags::UPDATE_AUTHTOK) && !flags.contains(PamFlags::SILENT) {
+		_ = pamh.pam_prompt(PamConvType::TextInfo, "Password changed");
+	}
+	PamResult::Success
+}
+
+#[test]
+fn module_flags() {
+	let mut pam = MockPam::new("passwd");
+	assert_eq!(pam.call(pam_sm_chauthtok, PamFlags::PRELIM_CHECK, &[]), PamResult::Success);
+	assert_eq!(pam.call(pam_sm_chauthtok, PamFlags::UPDATE_AUTHTOK | PamFlags::SILENT, &[]), PamResult::Success);
+	assert!(pam.messages().is_empty());
+	assert_eq!(pam.call(pam_sm_chauthtok, PamFlags::UPDATE_AUTHTOK, &[]), PamResult::Success);
+	assert_eq!(pam.messages(), [(PamConvType::TextInfo, "Password changed".to_string())]);
+
+	let flags = PamFlags::SILENT | PamFlags::from_bits_retain(0x100000);
+	assert_eq!(format!("{flags:?}"), "PamFlags(SILENT | 0x100000)");
+	assert_eq!(PamFlags::from_bits(flags.bits()), None);
+}
+
+#[test]
+fn fail_delay() {
+	let delays = Rc::new(RefCell::new(Vec::new()));
+	let seen = delays.clone();
+	let conv = PamConv::new(|_, _, _: &mut ()| PamResult::Success, ())
+		.fail_delay(move |res, delay| seen.borrow_mut().push((res, delay)));
+
+	// Setting the conversation sets the delay with it.
+	let mut pam = MockPam::new("login").item(PamItem::Conv(conv));
+	assert!(pam.fail_delay(PamResult::AuthErr, Duration::from_millis(1500)));
+	assert_eq!(*delays.borrow(), [(PamResult::AuthErr, Duration::from_millis(1500))]);
+
+	// Raw functions cannot be copied between handles without unsafe.
+	let pamh = pam.handle();
+	let Ok(PamItem::FailDelay(Some(delay))) = pamh.pam_get_item(PamItemType::FailDelay) else {
+		panic!("fail delay should be set");
+	};
+	let mut other = MockPam::new("login");
+	assert_eq!(other.handle().pam_set_item(PamItem::FailDelay(Some(delay))).err(), Some(PamResult::BadItem));
+	assert!(!other.fail_delay(PamResult::AuthErr, Duration::ZERO));
+
+	// A conversation without a delay clears it, since it expects the old data.
+	pamh.pam_set_item(PamItem::Conv(PamConv::new(|_, _, _: &mut ()| PamResult::Success, ()))).unwrap();
+	assert!(matches!(pamh.pam_get_item(PamItemType::FailDelay), Ok(PamItem::FailDelay(None))));
+	assert!(!pam.fail_delay(PamResult::AuthErr, Duration::ZERO));
+	assert_eq!(delays.borrow().len(), 1);
+}
+
+unsafe extern "C" fn raw_delay(status: i32, usec: u32, _data: *const ()) {
+	RAW_DELAY.set(Some((status, usec)));
+}
+thread_local! {
+	static RAW_DELAY: Cell<Option<(i32, u32)>> = const { Cell::new(None) };
+}
+
+#[test]
+fn raw_fail_delay() {
+	let mut pam = MockPam::new("login");
+	unsafe { pam.handle().pam_set_fail_delay(Some(raw_delay)) }.unwrap();
+	assert!(pam.fail_delay(PamResult::PermDenied, Duration::from_micros(20)));
+	assert_eq!(RAW_DELAY.get(), Some((PamResult::PermDenied as i32, 20)));
+
+	pam.handle().pam_set_item(PamItem::FailDelay(None)).unwrap();
+	assert!(!pam.fail_delay(PamResult::PermDenied, Duration::ZERO));
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "mock")]
+
+use pam::mock::MockPam;
+use pam::*;
+use std::ffi::CStr;
+use std::path::PathBuf;
+
+#[derive(Debug, Default, PamOptions, PartialEq)]
+struct Options {
+	debug: bool,
+	use_first_pass: bool,
+	timeout: u32,
+	file: Option<PathBuf>,
+}
+
+#[acct_mgmt]
+fn acct(pamh: &mut PamHandle, _flags: i32, opts: &Options) -> PamResult {
+	if opts.debug {
+		pamh.pam_syslog(PamLogLevel::Debug, format!("timeout={}", opts.timeout));
+	}
+
+	match opts.file {
+		Some(_) => PamResult::Success,
+		None => PamResult::Ignore,
+	}
+}
+
+#[test]
+fn parse_options() {
+	let args = [c"debug", c"timeout=30", c"file=/etc/x"];
+	assert_eq!(Options::parse(&args), Ok(Options {
+		debug: true,
+		use_first_pass: false,
+		timeout: 30,
+		file: Some("/etc/x".into()),
+	}));
+	assert_eq!(Options::parse(&[]), Ok(Options::default()));
+
+	let parse = |arg: &CStr| Options::parse(&[arg]);
+	assert_eq!(parse(c"nodebug"), Err(OptionError::Unknown("nodebug".into())));
+	assert_eq!(parse(c"debug=1"), Err(OptionError::Invalid("debug=1".into())));
+	assert_eq!(parse(c"timeout"), Err(OptionError::Invalid("timeout".into())));
+	assert_eq!(parse(c"timeout=soon"), Err(OptionError::Invalid("timeout=soon".into())));
+}
+
+#[test]
+fn module_options() {
+	let mut pam = MockPam::new("login");
+	assert_eq!(pam.call(pam_sm_acct_mgmt, PamFlags::empty(), &["debug", "timeout=5", "file=/etc/x"]), PamResult::Success);
+	assert_eq!(pam.call(pam_sm_acct_mgmt, PamFlags::empty(), &[]), PamResult::Ignore);
+	assert_eq!(pam.logs(), [(PamLogLevel::Debug, "timeout=5".to_string())]);
+
+	// Invalid options are logged, and the function is not called.
+	assert_eq!(pam.call(pam_sm_acct_mgmt, PamFlags::empty(), &["debug", "timeout=-1"]), PamResult::ServiceErr);
+	assert_eq!(pam.logs()[1], (PamLogLevel::Error, "Invalid option \"timeout=-1\"".to_string()));
+	assert_eq!(pam.logs().len(), 2);
+}
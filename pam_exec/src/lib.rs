@@ -29,7 +29,7 @@ macro_rules! debug {
 
 
 #[authenticate]
-fn auth(mut pamh: PamHandle, _flags: i32, _args: Vec<&ffi::CStr>) -> pam::PamResult {
+fn auth(pamh: &mut PamHandle, _flags: i32, _args: &[&ffi::CStr]) -> pam::PamResult {
 	// Start syslog (__ holds service cstring for function lifetime)
 	let log_opts = SyslogOpts::CONSOLE | SyslogOpts::PID | SyslogOpts::NODELAY;
 	let __ = openlog(MODULE_NAME, log_opts, SyslogFacility::Local1);
@@ -128,7 +128,7 @@ fn auth(mut pamh: PamHandle, _flags: i32, _args: Vec<&ffi::CStr>) -> pam::PamRes
 }
 
 #[setcred]
-fn setcred(_pamh: PamHandle, _flags: i32, _args: Vec<&ffi::CStr>) -> PamResult {
+fn setcred(_pamh: &mut PamHandle, _flags: i32, _args: &[&ffi::CStr]) -> PamResult {
 	/*
 	let log_opts = SyslogOpts::CONSOLE | SyslogOpts::PID | SyslogOpts::NODELAY;
 	let __ = openlog(MODULE_NAME, log_opts, SyslogFacility::Local1);
@@ -5,60 +5,233 @@
 //!
 //! # Function signature
 //! Functions must have the signature
-//!  `fn(pam::PamHandle, i32, Vec<&CStr>) -> pam::PamResult` in order to be
-//!  valid.
+//!  `fn(&mut pam::PamHandle, i32, &[&CStr]) -> pam::PamResult` in order to be
+//!  valid (or take options, see below). Flags may instead be taken as
+//!  `pam::PamFlags`, e.g. to check for `PamFlags::SILENT`. Other signatures
+//!  fail to compile, with errors pointing at the mismatched part. Types are
+//!  checked by name, so must not be renamed on import.
+//!
+//! # Panics
+//! Panics are caught rather than unwinding into libpam, logged, and the module
+//!  returns `PAM_SYSTEM_ERR`. Another result may be given as an argument,
+//!  e.g. `#[authenticate(on_panic = PamResult::Ignore)]`.
+//!
+//! # Options
+//! Taking `&T` in place of the arguments, where `T` implements
+//!  `pam::PamOptions` (e.g. with `#[derive(PamOptions)]`), parses the module
+//!  arguments into `T` before the call. Invalid arguments are logged, and the
+//!  module returns `PAM_SERVICE_ERR`.
 
 use proc_macro::TokenStream;
-use quote::quote_spanned;
-use syn::{ItemFn, parse_macro_input};
-use syn::spanned::Spanned;
+use proc_macro2::{Ident, Span, TokenStream as TokenStream2};
+use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::{parse_macro_input, Data, DataStruct, DeriveInput, Expr, Fields, FnArg, GenericArgument, ItemFn, PathArguments, ReturnType, Type};
 
-// NOTE: Two parameters required because anything_$bla will always fail (leading anything), so $bla is required.
-macro_rules! pam_wrapper {
-	($attr_name:ident, $lib_name:ident) => {
-		#[proc_macro_attribute]
-		pub fn $attr_name(_attr: TokenStream, item: TokenStream) -> TokenStream {
-			let input = parse_macro_input!(item as ItemFn);
-			let fn_ident = &input.sig.ident;
-
-			let input_span = input.sig.span();
-			let output = quote_spanned! {input_span=>
-				// Original function verbatim
-				#input
-
-				// PAM native function wrapper
-				#[no_mangle]
-				pub extern "C" fn $lib_name(
-					pamh: *const (),
-					flags: std::ffi::c_int,
-					argc: std::ffi::c_int,
-					argv: *const *const std::ffi::c_char
-				) -> std::ffi::c_int {
-					use std::ffi::CStr;
-					use std::vec::Vec;
-					use std::mem::ManuallyDrop;
-					use std::ops::DerefMut;
-
-					// Collect arguments into vector
-					let args: Vec<&CStr> = unsafe {
-						(0..argc).map(|i| CStr::from_ptr(argv.add(i as usize) as _)).collect()
-					};
+/// Type with any invisible grouping (e.g. from `macro_rules!`) removed.
+fn ungroup(ty: &Type) -> &Type {
+	match ty {
+		Type::Group(g) => ungroup(&g.elem),
+		Type::Paren(p) => ungroup(&p.elem),
+		ty => ty,
+	}
+}
 
-					// Type check (more useful errors than just calling function).
-					type Expected = fn(&mut pam::PamHandle, i32, &[&CStr]) -> pam::PamResult;
-					let f: Expected = #fn_ident;
+/// Whether `ty` is a path ending in `name`, without generic arguments other
+///  than lifetimes (e.g. `pam::PamHandle<'_>`).
+fn is_named(ty: &Type, name: &str) -> bool {
+	let Type::Path(path) = ungroup(ty) else {
+		return false
+	};
 
-					// Create pam handle object. Intentionally do not drop pam handle since modules
-					//  should not close the pam handle passed in.
-					// SAFETY: Pointer is guaranteed valid as a function parameter.
-					let mut pamh = ManuallyDrop::new(unsafe { pam::PamHandle::from_raw(pamh) });
+	path.qself.is_none() && path.path.segments.last().is_some_and(|seg| {
+		seg.ident == name && match &seg.arguments {
+			PathArguments::None => true,
+			PathArguments::AngleBracketed(args) => args.args.iter().all(|a| matches!(a, GenericArgument::Lifetime(_))),
+			PathArguments::Parenthesized(_) => false,
+		}
+	})
+}
 
-					// Call function with values, and convert return value
-					f(pamh.deref_mut(), flags, &args[..]) as i32
-				}
-			};
+/// Whether `ty` is `&mut PamHandle`.
+fn is_handle(ty: &Type) -> bool {
+	matches!(ungroup(ty), Type::Reference(r) if r.mutability.is_some() && is_named(&r.elem, "PamHandle"))
+}
+
+/// Whether `ty` is `i32` or `PamFlags`.
+fn is_flags(ty: &Type) -> bool {
+	is_named(ty, "i32") || is_named(ty, "PamFlags")
+}
+
+/// How the user function takes module arguments.
+enum ArgsKind<'a> {
+	/// Raw arguments, as `&[&CStr]`.
+	Raw,
+	/// Options `T`, as `&T`.
+	Options(&'a Type),
+}
+impl<'a> ArgsKind<'a> {
+	fn of(ty: &'a Type) -> Option<Self> {
+		let Type::Reference(r) = ungroup(ty) else {
+			return None
+		};
+		if r.mutability.is_some() {
+			return None;
+		}
+
+		match ungroup(&r.elem) {
+			Type::Slice(slice) => match ungroup(&slice.elem) {
+				Type::Reference(arg) if arg.mutability.is_none() && is_named(&arg.elem, "CStr") => Some(Self::Raw),
+				_ => None,
+			},
+			_ => Some(Self::Options(&r.elem)),
+		}
+	}
+}
 
+/// Expected signature, for errors.
+const SIGNATURE: &str = "expected `fn(&mut PamHandle, i32, &[&CStr]) -> PamResult`, with flags optionally `PamFlags`, and `&T` for options `T` as the last parameter";
+
+/// Generate the exported `lib_name` function, calling the user function.
+fn wrap(attr: TokenStream, item: TokenStream, lib_name: &str) -> TokenStream {
+	let mut on_panic = None;
+	let attr_parser = syn::meta::parser(|meta| {
+		if meta.path.is_ident("on_panic") {
+			on_panic = Some(meta.value()?.parse::<Expr>()?);
+			Ok(())
+		} else {
+			Err(meta.error("unsupported argument, expected `on_panic = <PamResult>`"))
+		}
+	});
+	parse_macro_input!(attr with attr_parser);
+	let input = parse_macro_input!(item as ItemFn);
+
+	match wrapper(&input, on_panic, lib_name) {
+		Ok(output) => output.into(),
+		// Keep the function, so errors are only about the signature.
+		Err(e) => {
+			let mut output = input.to_token_stream();
+			output.extend(e.to_compile_error());
 			output.into()
+		},
+	}
+}
+
+fn wrapper(input: &ItemFn, on_panic: Option<Expr>, lib_name: &str) -> syn::Result<TokenStream2> {
+	let sig = &input.sig;
+	if let Some(asyncness) = &sig.asyncness {
+		return Err(syn::Error::new_spanned(asyncness, "module functions cannot be async"));
+	}
+	if !sig.generics.params.is_empty() {
+		return Err(syn::Error::new_spanned(&sig.generics, "module functions cannot be generic"));
+	}
+	if let Some(variadic) = &sig.variadic {
+		return Err(syn::Error::new_spanned(variadic, SIGNATURE));
+	}
+	if sig.inputs.len() != 3 {
+		return Err(syn::Error::new(sig.paren_token.span.join(), SIGNATURE));
+	}
+	match &sig.output {
+		ReturnType::Type(_, ty) if is_named(ty, "PamResult") => {},
+		ReturnType::Type(_, ty) => return Err(syn::Error::new_spanned(ty, "module functions must return `PamResult`")),
+		ReturnType::Default => return Err(syn::Error::new(sig.paren_token.span.close(), "module functions must return `PamResult`")),
+	}
+	let tys = sig.inputs.iter()
+		.map(|arg| match arg {
+			FnArg::Typed(pat) => Ok(&*pat.ty),
+			FnArg::Receiver(r) => Err(syn::Error::new_spanned(r, "module functions cannot take `self`")),
+		})
+		.collect::<syn::Result<Vec<_>>>()?;
+
+	// Types are checked here, since mismatches in the generated call give
+	//  errors (and suggestions) that make no sense for the user function.
+	if !is_handle(tys[0]) {
+		return Err(syn::Error::new_spanned(tys[0], SIGNATURE));
+	}
+	if !is_flags(tys[1]) {
+		return Err(syn::Error::new_spanned(tys[1], SIGNATURE));
+	}
+	let Some(args_kind) = ArgsKind::of(tys[2]) else {
+		return Err(syn::Error::new_spanned(tys[2], SIGNATURE));
+	};
+
+	let flags = match is_named(tys[1], "PamFlags") {
+		true => quote! { pam::PamFlags::from_bits_retain(flags) },
+		false => quote! { flags },
+	};
+	let (parse, args) = match args_kind {
+		ArgsKind::Options(ty) => (quote! {
+			let opts = match <#ty as pam::PamOptions>::parse(&args[..]) {
+				Ok(o) => o,
+				Err(e) => {
+					pamh.pam_syslog(pam::PamLogLevel::Error, e.to_string());
+					return pam::PamResult::ServiceErr
+				},
+			};
+		}, quote! { &opts }),
+		ArgsKind::Raw => (quote! {}, quote! { &args[..] }),
+	};
+	let fn_ident = &sig.ident;
+	let call = quote! { #fn_ident(pamh.deref_mut(), #flags, #args) };
+
+	let on_panic = on_panic.map_or(quote! { pam::PamResult::SystemErr }, |e| e.to_token_stream());
+	let panic_msg = format!("{lib_name} panicked");
+	let lib_name = Ident::new(lib_name, Span::call_site());
+
+	Ok(quote! {
+		// Original function verbatim
+		#input
+
+		// PAM native function wrapper
+		/// # Safety
+		/// Arguments must be as given by libpam to service modules.
+		#[no_mangle]
+		pub unsafe extern "C" fn #lib_name(
+			pamh: *const (),
+			flags: std::ffi::c_int,
+			argc: std::ffi::c_int,
+			argv: *const *const std::ffi::c_char
+		) -> std::ffi::c_int {
+			use std::ffi::CStr;
+			use std::vec::Vec;
+			use std::mem::ManuallyDrop;
+			use std::ops::DerefMut;
+
+			let on_panic: pam::PamResult = #on_panic;
+
+			// Collect arguments into vector
+			let args: Vec<&CStr> = unsafe {
+				(0..argc).map(|i| CStr::from_ptr(*argv.add(i as usize))).collect()
+			};
+
+			// Create pam handle object. Intentionally do not drop pam handle since modules
+			//  should not close the pam handle passed in.
+			// SAFETY: Pointer is guaranteed valid as a function parameter.
+			let mut pamh = ManuallyDrop::new(unsafe { pam::PamHandle::from_raw(pamh) });
+
+			// Call function with values, and convert return value. Unwinding into
+			//  libpam is undefined, so panics are caught.
+			let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| -> pam::PamResult {
+				#parse
+				#call
+			}));
+			match res {
+				Ok(r) => r as i32,
+				Err(_) => {
+					pamh.pam_syslog(pam::PamLogLevel::Error, #panic_msg);
+					on_panic as i32
+				},
+			}
+		}
+	})
+}
+
+// NOTE: Two parameters required because anything_$bla will always fail (leading anything), so $bla is required.
+macro_rules! pam_wrapper {
+	($attr_name:ident, $lib_name:ident) => {
+		#[proc_macro_attribute]
+		pub fn $attr_name(attr: TokenStream, item: TokenStream) -> TokenStream {
+			wrap(attr, item, stringify!($lib_name))
 		}
 	};
 }
@@ -69,3 +242,90 @@ pam_wrapper!(chauthtok, pam_sm_chauthtok);
 pam_wrapper!(close_session, pam_sm_close_session);
 pam_wrapper!(open_session, pam_sm_open_session);
 pam_wrapper!(setcred, pam_sm_setcred);
+
+/// How an option field is given.
+enum OptionKind {
+	/// `bool`, given as `name`.
+	Flag,
+	/// `Option<T>`, given as `name=value`.
+	Optional,
+	/// Anything else, given as `name=value`.
+	Value,
+}
+impl OptionKind {
+	fn of(ty: &Type) -> Self {
+		let Type::Path(path) = ty else {
+			return Self::Value
+		};
+
+		match path.path.segments.last() {
+			Some(seg) if seg.ident == "bool" && seg.arguments.is_empty() => Self::Flag,
+			Some(seg) if seg.ident == "Option" && matches!(seg.arguments, PathArguments::AngleBracketed(_)) => Self::Optional,
+			_ => Self::Value,
+		}
+	}
+}
+
+/// Derive `pam::PamOptions` for a struct with named fields, each an option of
+///  the same name. The struct must implement `Default`, for options not given.
+///
+/// `bool` fields are flags, and others take a value parsed with `FromStr`
+///  (with `Option<T>` parsing `T`).
+#[proc_macro_derive(PamOptions)]
+pub fn derive_pam_options(item: TokenStream) -> TokenStream {
+	let input = parse_macro_input!(item as DeriveInput);
+	let Data::Struct(DataStruct { fields: Fields::Named(fields), .. }) = &input.data else {
+		return syn::Error::new_spanned(&input.ident, "PamOptions can only be derived for structs with named fields")
+			.to_compile_error()
+			.into()
+	};
+
+	let mut names = Vec::new();
+	let arms: Vec<TokenStream2> = fields.named.iter().map(|field| {
+		let ident = field.ident.as_ref().unwrap();
+		let name = ident.unraw().to_string();
+		names.push(name.clone());
+
+		match OptionKind::of(&field.ty) {
+			OptionKind::Flag => quote! {
+				(#name, None) => opts.#ident = true,
+			},
+			OptionKind::Optional => quote! {
+				(#name, Some(v)) => opts.#ident = Some(v.parse().map_err(|_| invalid())?),
+			},
+			OptionKind::Value => quote! {
+				(#name, Some(v)) => opts.#ident = v.parse().map_err(|_| invalid())?,
+			},
+		}
+	}).collect();
+
+	let ident = &input.ident;
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+	let output = quote! {
+		impl #impl_generics pam::PamOptions for #ident #ty_generics #where_clause {
+			fn parse(args: &[&std::ffi::CStr]) -> std::result::Result<Self, pam::OptionError> {
+				const NAMES: &[&str] = &[#(#names),*];
+
+				let mut opts = <Self as std::default::Default>::default();
+				for arg in args {
+					let arg = arg.to_string_lossy();
+					let invalid = || pam::OptionError::Invalid(arg.to_string());
+					let (name, value) = match arg.split_once('=') {
+						Some((name, value)) => (name, Some(value)),
+						None => (&arg[..], None),
+					};
+
+					match (name, value) {
+						#(#arms)*
+						_ if NAMES.contains(&name) => return Err(invalid()),
+						_ => return Err(pam::OptionError::Unknown(arg.to_string())),
+					}
+				}
+
+				Ok(opts)
+			}
+		}
+	};
+
+	output.into()
+}
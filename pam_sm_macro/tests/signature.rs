@@ -0,0 +1,6 @@
+#[test]
+fn signatures() {
+	let t = trybuild::TestCases::new();
+	t.pass("tests/ui/valid.rs");
+	t.compile_fail("tests/ui/invalid_*.rs");
+}
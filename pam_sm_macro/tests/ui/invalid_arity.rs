@@ -0,0 +1,14 @@
+use pam::*;
+use std::ffi::CStr;
+
+#[authenticate]
+fn two(_pamh: &mut PamHandle, _flags: i32) -> PamResult {
+	PamResult::Success
+}
+
+#[setcred]
+fn four(_pamh: &mut PamHandle, _flags: i32, _args: &[&CStr], _extra: i32) -> PamResult {
+	PamResult::Success
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use pam::*;
+use std::ffi::CStr;
+
+#[authenticate]
+async fn auth(_pamh: &mut PamHandle<'_>, _flags: i32, _args: &[&CStr]) -> PamResult {
+	PamResult::Success
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use pam::*;
+
+#[authenticate]
+fn auth<T: PamOptions>(_pamh: &mut PamHandle, _flags: i32, _opts: &T) -> PamResult {
+	PamResult::Success
+}
+
+fn main() {}
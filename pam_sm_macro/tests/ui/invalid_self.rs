@@ -0,0 +1,12 @@
+use pam::*;
+use std::ffi::CStr;
+
+struct Module;
+impl Module {
+	#[authenticate]
+	fn auth(&self, _flags: i32, _args: &[&CStr]) -> PamResult {
+		PamResult::Success
+	}
+}
+
+fn main() {}
@@ -0,0 +1,28 @@
+use pam::*;
+use std::ffi::CStr;
+
+// Signature from before handles were borrowed.
+#[authenticate]
+fn auth(_pamh: PamHandle, _flags: i32, _args: Vec<&CStr>) -> PamResult {
+	PamResult::Success
+}
+
+#[setcred]
+fn setcred(_pamh: &mut PamHandle, _flags: u8, _args: &[&CStr]) -> PamResult {
+	PamResult::Success
+}
+
+#[open_session]
+fn open(_pamh: &mut PamHandle, _flags: i32, _args: &[String]) -> PamResult {
+	PamResult::Success
+}
+
+#[close_session]
+fn close(_pamh: &mut PamHandle, _flags: i32, _args: &[&CStr]) -> i32 {
+	0
+}
+
+#[acct_mgmt]
+fn acct(_pamh: &mut PamHandle, _flags: i32, _args: &[&CStr]) {}
+
+fn main() {}
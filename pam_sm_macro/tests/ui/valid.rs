@@ -0,0 +1,22 @@
+use pam::*;
+use std::ffi::CStr;
+
+#[derive(Default, PamOptions)]
+struct Options {
+	debug: bool,
+}
+
+#[authenticate]
+fn auth(_pamh: &mut PamHandle, _flags: i32, _args: &[&CStr]) -> PamResult {
+	PamResult::Success
+}
+
+#[setcred(on_panic = PamResult::Ignore)]
+fn setcred(_pamh: &mut pam::PamHandle<'_>, _flags: PamFlags, opts: &Options) -> pam::PamResult {
+	match opts.debug {
+		true => PamResult::Ignore,
+		false => PamResult::Success,
+	}
+}
+
+fn main() {}